@@ -8,7 +8,11 @@ use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use byteorder::{LittleEndian, WriteBytesExt};
-use icosahedron::Polyhedron;
+use icosahedron::{
+    Bounds, Colormap, FaceAttribute, LineMesh, NormalMode, PointDistribution, Polyhedron,
+    Triangulation, UvMode,
+};
+use serde::Serialize;
 
 fn write_to_binary_file(polyhedron: Polyhedron, path: &Path) {
     let bin_file = File::create(path).expect("Can't create file");
@@ -64,6 +68,59 @@ fn write_to_binary_file(polyhedron: Polyhedron, path: &Path) {
             .write_u32::<LittleEndian>(cell.c as u32)
             .expect(write_error_message);
     }
+    if polyhedron.cell_faces.is_empty() {
+        writer
+            .write_u32::<LittleEndian>(0)
+            .expect(write_error_message);
+    } else {
+        writer
+            .write_u32::<LittleEndian>(polyhedron.face_colors.len() as u32)
+            .expect(write_error_message);
+        for color in polyhedron.face_colors.iter() {
+            writer
+                .write_f32::<LittleEndian>(color.0.x)
+                .expect(write_error_message);
+            writer
+                .write_f32::<LittleEndian>(color.0.y)
+                .expect(write_error_message);
+            writer
+                .write_f32::<LittleEndian>(color.0.z)
+                .expect(write_error_message);
+        }
+        for face_index in polyhedron.cell_faces.iter() {
+            writer
+                .write_u32::<LittleEndian>(*face_index as u32)
+                .expect(write_error_message);
+        }
+    }
+    writer
+        .write_u32::<LittleEndian>(polyhedron.uvs.len() as u32)
+        .expect(write_error_message);
+    for uv in polyhedron.uvs.iter() {
+        writer
+            .write_f32::<LittleEndian>(uv.0.x)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(uv.0.y)
+            .expect(write_error_message);
+    }
+    writer
+        .write_u32::<LittleEndian>(polyhedron.tangents.len() as u32)
+        .expect(write_error_message);
+    for tangent in polyhedron.tangents.iter() {
+        writer
+            .write_f32::<LittleEndian>(tangent.0.x)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(tangent.0.y)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(tangent.0.z)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(tangent.0.w)
+            .expect(write_error_message);
+    }
 }
 
 fn write_to_json_file(polyhedron: Polyhedron, path: &Path) {
@@ -74,17 +131,122 @@ fn write_to_json_file(polyhedron: Polyhedron, path: &Path) {
         .expect("Can't write to file");
 }
 
+fn write_to_file(polyhedron: Polyhedron, format: &Format, path: &Path) {
+    match format {
+        Format::Bin => write_to_binary_file(polyhedron, path),
+        Format::Json => write_to_json_file(polyhedron, path),
+    };
+}
+
+fn write_lines_to_binary_file(lines: LineMesh, path: &Path) {
+    let bin_file = File::create(path).expect("Can't create file");
+    let mut writer = BufWriter::new(bin_file);
+    let write_error_message = "Error encountered while writing to binary file";
+    writer
+        .write_u32::<LittleEndian>(lines.positions.len() as u32)
+        .expect(write_error_message);
+    writer
+        .write_u32::<LittleEndian>(lines.lines.len() as u32)
+        .expect(write_error_message);
+    for position in lines.positions.iter() {
+        writer
+            .write_f32::<LittleEndian>(position.0.x)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(position.0.y)
+            .expect(write_error_message);
+        writer
+            .write_f32::<LittleEndian>(position.0.z)
+            .expect(write_error_message);
+    }
+    for line in lines.lines.iter() {
+        writer
+            .write_u32::<LittleEndian>(line[0] as u32)
+            .expect(write_error_message);
+        writer
+            .write_u32::<LittleEndian>(line[1] as u32)
+            .expect(write_error_message);
+    }
+}
+
+fn write_lines_to_json_file(lines: LineMesh, path: &Path) {
+    let mut json_file = File::create(path).expect("Can't create file");
+    let json = serde_json::to_string(&lines).expect("Problem serializing");
+    json_file
+        .write_all(json.as_bytes())
+        .expect("Can't write to file");
+}
+
+fn write_borders(polyhedron: &Polyhedron, format: &Format, borders: &Borders, path: &Path) {
+    match borders.style {
+        BorderStyle::Lines => {
+            let lines = polyhedron.face_edge_lines();
+            println!("border lines: {}", lines.lines.len());
+            match format {
+                Format::Bin => write_lines_to_binary_file(lines, path),
+                Format::Json => write_lines_to_json_file(lines, path),
+            };
+        }
+        BorderStyle::Ribbons => {
+            let ribbons = polyhedron.face_edge_ribbons(borders.width, borders.width / 2.0);
+            println!("border ribbons: {}", ribbons.face_count());
+            write_to_file(ribbons, format, path);
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ChunkManifestEntry {
+    id: usize,
+    file: String,
+    faces: Vec<usize>,
+    bounds: Bounds,
+}
+
+fn write_chunks(polyhedron: Polyhedron, format: &Format, dir: &str, name: &str, level: u32) {
+    let mut manifest = vec![];
+    for chunk in polyhedron.chunks(level) {
+        let file = format!("{}_c{}.{}", name, chunk.id, format.extension());
+        write_to_file(chunk.polyhedron, format, &Path::new(dir).join(&file));
+        manifest.push(ChunkManifestEntry {
+            id: chunk.id,
+            file,
+            faces: chunk.faces,
+            bounds: chunk.bounds,
+        });
+    }
+    println!("chunks: {}", manifest.len());
+
+    let mut manifest_file = File::create(Path::new(dir).join(format!("{}_chunks.json", name)))
+        .expect("Can't create file");
+    let json = serde_json::to_string(&manifest).expect("Problem serializing");
+    manifest_file
+        .write_all(json.as_bytes())
+        .expect("Can't write to file");
+}
+
 fn generate_files(
     dir: &str,
     format: Format,
-    truncated: bool,
-    colored: bool,
+    options: MeshOptions,
+    chunk_level: Option<u32>,
     param_list: Vec<(f32, u32)>,
 ) {
-    let mesh_type = if truncated {
-        "hexsphere"
-    } else {
-        "icosahedron"
+    let MeshOptions {
+        truncated,
+        triangulation,
+        normals,
+        uvs,
+        tangents,
+        coloring,
+        borders,
+        points,
+    } = options;
+    let mesh_type = match (truncated, &points) {
+        (true, None) => "hexsphere".to_string(),
+        (false, None) => "icosahedron".to_string(),
+        (true, Some(points)) => format!("voronoi_{}", points.name()),
+        (false, Some(points)) => format!("delaunay_{}", points.name()),
     };
 
     for param in param_list {
@@ -93,39 +255,96 @@ fn generate_files(
             mesh_type, param.0, param.1
         );
 
-        let polyhedron = if truncated {
-            let mut hexsphere = Polyhedron::new_truncated_isocahedron(param.0, param.1);
-            hexsphere.compute_triangle_normals();
-            hexsphere
+        let normal_mode = normals.normal_mode();
+        let mut polyhedron = if let Some(points) = &points {
+            let points = points.distribution(param.1).points();
+            if truncated {
+                Polyhedron::new_spherical_voronoi(param.0, &points, triangulation.triangulation())
+            } else {
+                Polyhedron::new_spherical_delaunay(param.0, &points)
+            }
+        } else if truncated {
+            Polyhedron::new_truncated_isocahedron_with_triangulation(
+                param.0,
+                param.1,
+                triangulation.triangulation(),
+            )
         } else {
-            let mut icosahedron = Polyhedron::new_isocahedron(param.0, param.1);
-            icosahedron.compute_triangle_normals();
-            icosahedron
+            Polyhedron::new_isocahedron(param.0, param.1)
         };
+        if normal_mode == NormalMode::Flat {
+            // flat shading needs vertices that aren't shared between faces
+            if coloring.is_none() {
+                let mut unshared = Polyhedron::new();
+                unshared.unique_face_vertices(polyhedron);
+                polyhedron = unshared;
+            }
+        } else {
+            polyhedron.compute_normals(normal_mode);
+        }
 
-        let colored_polyhedron = if colored {
-            let mut colored = Polyhedron::new();
-            colored.unique_vertices(polyhedron);
-            colored.assign_random_face_colors();
+        let mut colored_polyhedron = if let Some(coloring) = &coloring {
+            let mut colored = match coloring.mode {
+                ColorMode::Vertex => {
+                    let mut colored = Polyhedron::new();
+                    colored.unique_vertices(polyhedron);
+                    colored
+                }
+                ColorMode::Face => {
+                    let mut colored = Polyhedron::new();
+                    colored.unique_face_vertices(polyhedron);
+                    colored
+                }
+                ColorMode::Indexed => {
+                    let mut colored = if normal_mode == NormalMode::Flat {
+                        let mut unshared = Polyhedron::new();
+                        unshared.unique_face_vertices(polyhedron);
+                        unshared
+                    } else {
+                        polyhedron
+                    };
+                    colored.assign_cell_faces();
+                    colored
+                }
+            };
+            match coloring.color_by.attribute() {
+                Some(attribute) => {
+                    colored.color_faces_by_attribute(attribute, &coloring.colormap.colormap())
+                }
+                None => colored.assign_random_face_colors(),
+            }
+            // the face color table is only written for meshes indexed by cell_faces
+            if colored.cell_faces.is_empty() {
+                colored.face_colors.clear();
+            }
             colored
         } else {
             polyhedron
         };
+        if normal_mode == NormalMode::Flat {
+            colored_polyhedron.compute_normals(normal_mode);
+        }
+        if let Some(uvs) = &uvs {
+            colored_polyhedron.compute_uvs(uvs.uv_mode());
+        }
+        if tangents {
+            colored_polyhedron.compute_tangents();
+        }
 
         println!("triangles: {}", colored_polyhedron.cells.len());
         println!("vertices: {}", colored_polyhedron.positions.len());
 
-        let filename = Path::new(dir).join(format!(
-            "{}_r{}_d{}.{}",
-            mesh_type,
-            param.0,
-            param.1,
-            format.extension()
-        ));
-        match format {
-            Format::Bin => write_to_binary_file(colored_polyhedron, &filename),
-            Format::Json => write_to_json_file(colored_polyhedron, &filename),
-        };
+        let name = format!("{}_r{}_d{}", mesh_type, param.0, param.1);
+        if let Some(borders) = &borders {
+            let filename = Path::new(dir).join(format!("{}_borders.{}", name, format.extension()));
+            write_borders(&colored_polyhedron, &format, borders, &filename);
+        }
+        if let Some(level) = chunk_level {
+            write_chunks(colored_polyhedron, &format, dir, &name, level);
+        } else {
+            let filename = Path::new(dir).join(format!("{}.{}", name, format.extension()));
+            write_to_file(colored_polyhedron, &format, &filename);
+        }
     }
 }
 
@@ -137,6 +356,182 @@ arg_enum! {
     }
 }
 
+arg_enum! {
+    #[derive(Debug)]
+    enum TriangulationName {
+        Spokes,
+        CenterFan,
+        MinimalFan,
+    }
+}
+
+impl TriangulationName {
+    fn triangulation(&self) -> Triangulation {
+        match self {
+            TriangulationName::Spokes => Triangulation::Spokes,
+            TriangulationName::CenterFan => Triangulation::CenterFan,
+            TriangulationName::MinimalFan => Triangulation::MinimalFan,
+        }
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum NormalModeName {
+        Analytic,
+        Smooth,
+        Flat,
+    }
+}
+
+impl NormalModeName {
+    fn normal_mode(&self) -> NormalMode {
+        match self {
+            NormalModeName::Analytic => NormalMode::Analytic,
+            NormalModeName::Smooth => NormalMode::Smooth,
+            NormalModeName::Flat => NormalMode::Flat,
+        }
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum UvModeName {
+        Equirectangular,
+        CubeMap,
+        TileAtlas,
+    }
+}
+
+impl UvModeName {
+    fn uv_mode(&self) -> UvMode {
+        match self {
+            UvModeName::Equirectangular => UvMode::Equirectangular,
+            UvModeName::CubeMap => UvMode::CubeMap,
+            UvModeName::TileAtlas => UvMode::TileAtlas,
+        }
+    }
+}
+
+struct MeshOptions {
+    truncated: bool,
+    triangulation: TriangulationName,
+    normals: NormalModeName,
+    uvs: Option<UvModeName>,
+    tangents: bool,
+    coloring: Option<Coloring>,
+    borders: Option<Borders>,
+    points: Option<Points>,
+}
+
+/// Scattered points to triangulate instead of the regular icosahedron.
+struct Points {
+    distribution: PointsName,
+    seed: u64,
+    jitter: f32,
+}
+
+impl Points {
+    fn name(&self) -> String {
+        match self.distribution {
+            PointsName::Fibonacci => "fibonacci".to_string(),
+            _ => format!("{}_s{}", self.distribution, self.seed).to_lowercase(),
+        }
+    }
+
+    /// Distribution with as many points as the icosahedron of the same detail has vertices.
+    fn distribution(&self, detail: u32) -> PointDistribution {
+        let count = 10 * 4_usize.pow(detail) + 2;
+        match self.distribution {
+            PointsName::Fibonacci => PointDistribution::Fibonacci { count },
+            PointsName::PoissonDisk => PointDistribution::poisson_disk_with_count(count, self.seed),
+            PointsName::Jittered => PointDistribution::JitteredIsocahedron {
+                detail,
+                jitter: self.jitter,
+                seed: self.seed,
+            },
+        }
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum PointsName {
+        Fibonacci,
+        PoissonDisk,
+        Jittered,
+    }
+}
+
+struct Borders {
+    style: BorderStyle,
+    /// Width of ribbons in the same units as the radius.
+    width: f32,
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum BorderStyle {
+        Lines,
+        Ribbons,
+    }
+}
+
+struct Coloring {
+    color_by: ColorBy,
+    colormap: ColormapName,
+    mode: ColorMode,
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum ColorMode {
+        Vertex,
+        Face,
+        Indexed,
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum ColorBy {
+        Random,
+        Latitude,
+        TileType,
+        Area,
+    }
+}
+
+impl ColorBy {
+    fn attribute(&self) -> Option<FaceAttribute> {
+        match self {
+            ColorBy::Random => None,
+            ColorBy::Latitude => Some(FaceAttribute::Latitude),
+            ColorBy::TileType => Some(FaceAttribute::TileType),
+            ColorBy::Area => Some(FaceAttribute::Area),
+        }
+    }
+}
+
+arg_enum! {
+    #[derive(Debug)]
+    enum ColormapName {
+        Viridis,
+        Magma,
+        Grayscale,
+    }
+}
+
+impl ColormapName {
+    fn colormap(&self) -> Colormap {
+        match self {
+            ColormapName::Viridis => Colormap::Viridis,
+            ColormapName::Magma => Colormap::Magma,
+            ColormapName::Grayscale => Colormap::Grayscale,
+        }
+    }
+}
+
 impl Format {
     fn extension(&self) -> String {
         match self {
@@ -154,16 +549,10 @@ fn main() {
                 if metadata.is_dir() {
                     Ok(())
                 } else {
-                    Err(String::from(format!(
-                        "Output '{}' is not a directory",
-                        &path_clone
-                    )))
+                    Err(format!("Output '{}' is not a directory", &path_clone))
                 }
             }
-            Err(_) => Err(String::from(format!(
-                "Directory '{}' doesn't exist",
-                &path_clone
-            ))),
+            Err(_) => Err(format!("Directory '{}' doesn't exist", &path_clone)),
         }
     };
 
@@ -172,13 +561,54 @@ fn main() {
         (author: "Tyler Hallada <tyler@hallada.net>")
         (about: "Generates 3D icosahedra meshes")
         (@arg truncated: -t --truncated "Generate truncated icosahedra (hexspheres).")
+        (@arg triangulation: --triangulation +takes_value
+            possible_values(&TriangulationName::variants()) default_value("Spokes")
+            "How hexsphere faces are split into triangles: 2 per corner, a fan from the \
+            face center, or a fan from the first corner.")
+        (@arg normals: -n --normals +takes_value possible_values(&NormalModeName::variants())
+            default_value("Smooth")
+            "How normals are computed: pointing out from the sphere's center, averaged \
+            around each vertex, or flat across each face.")
+        (@arg uvs: -u --uvs +takes_value possible_values(&UvModeName::variants())
+            "Generates texture coordinates with the given mapping (splits vertices along \
+            texture seams).")
+        (@arg tangents: --tangents "Generates tangents for normal mapping, following the \
+            texture coordinates when --uvs is given and pointing east otherwise.")
         (@arg colored: -c --colored "Assigns a random color to every face \
             (increases vertices count).")
+        (@arg color_by: --("color-by") +takes_value possible_values(&ColorBy::variants())
+            "Face attribute to color faces by (implies --colored). \
+            Random by default.")
+        (@arg colormap: --colormap +takes_value possible_values(&ColormapName::variants())
+            default_value("Viridis")
+            "Colormap used by --color-by.")
+        (@arg color_mode: --("color-mode") +takes_value possible_values(&ColorMode::variants())
+            default_value("Vertex")
+            "How colored meshes store colors: unique vertices per triangle, unique vertices \
+            per face, or a per-face color table indexed by each triangle's face.")
+        (@arg borders: --borders +takes_value possible_values(&BorderStyle::variants())
+            "Also writes the outlines of the faces to a separate file, as line segments or \
+            as triangle ribbons raised slightly above the surface.")
+        (@arg border_width: --("border-width") +takes_value default_value("0.005")
+            "Width of border ribbons, as a fraction of the radius.")
+        (@arg points: --points +takes_value possible_values(&PointsName::variants())
+            "Triangulates scattered points instead of subdividing the icosahedron, with as \
+            many points as the icosahedron has vertices at each detail level. With \
+            --truncated the faces are the Voronoi cells around the points, giving irregular \
+            hex-like tiles.")
+        (@arg seed: --seed +takes_value default_value("0")
+            "Seed for the PoissonDisk and Jittered points.")
+        (@arg jitter: --jitter +takes_value default_value("0.3")
+            "How far Jittered points move, as a fraction of the edge length.")
         (@arg detail: -d --detail +takes_value default_value("7")
             "Maximum detail level to generate. \
             Each level multiplies the number of triangles by 4.")
         (@arg radius: -r --radius +takes_value default_value("1.0")
             "Radius of the polyhedron,")
+        (@arg chunk_level: --("chunk-level") +takes_value
+            "Splits every polyhedron into up to 20 * 4^level chunks along the faces of the \
+            base icosahedron, written as separate files with a JSON manifest of their bounds. \
+            Chunks without any faces are skipped.")
         (@arg format: -f --format +takes_value possible_values(&Format::variants())
             default_value("Bin")
             "Format to write the files in.")
@@ -188,10 +618,40 @@ fn main() {
     .get_matches();
 
     let truncated = matches.is_present("truncated");
-    let colored = matches.is_present("colored");
+    let uvs = value_t!(matches.value_of("uvs"), UvModeName).ok();
+    let tangents = matches.is_present("tangents");
+    let normals =
+        value_t!(matches.value_of("normals"), NormalModeName).unwrap_or(NormalModeName::Smooth);
+    let triangulation = value_t!(matches.value_of("triangulation"), TriangulationName)
+        .unwrap_or(TriangulationName::Spokes);
+    let colored = matches.is_present("colored") || matches.is_present("color_by");
+    let coloring = if colored {
+        Some(Coloring {
+            color_by: value_t!(matches.value_of("color_by"), ColorBy).unwrap_or(ColorBy::Random),
+            colormap: value_t!(matches.value_of("colormap"), ColormapName)
+                .unwrap_or(ColormapName::Viridis),
+            mode: value_t!(matches.value_of("color_mode"), ColorMode).unwrap_or(ColorMode::Vertex),
+        })
+    } else {
+        None
+    };
     let detail = value_t!(matches.value_of("detail"), u32).unwrap_or(7);
     let radius = value_t!(matches.value_of("radius"), f32).unwrap_or(1.0);
+    let borders = value_t!(matches.value_of("borders"), BorderStyle)
+        .ok()
+        .map(|style| Borders {
+            style,
+            width: value_t!(matches.value_of("border_width"), f32).unwrap_or(0.005) * radius,
+        });
+    let points = value_t!(matches.value_of("points"), PointsName)
+        .ok()
+        .map(|distribution| Points {
+            distribution,
+            seed: value_t!(matches.value_of("seed"), u64).unwrap_or(0),
+            jitter: value_t!(matches.value_of("jitter"), f32).unwrap_or(0.3),
+        });
     let format = value_t!(matches.value_of("format"), Format).unwrap_or(Format::Bin);
+    let chunk_level = value_t!(matches.value_of("chunk_level"), u32).ok();
     let output = matches.value_of("output").unwrap_or("output/");
 
     let param_list = |detail: u32, radius: f32| -> Vec<(f32, u32)> {
@@ -205,8 +665,17 @@ fn main() {
     generate_files(
         output,
         format,
-        truncated,
-        colored,
+        MeshOptions {
+            truncated,
+            triangulation,
+            normals,
+            uvs,
+            tangents,
+            coloring,
+            borders,
+            points,
+        },
+        chunk_level,
         param_list(detail, radius),
     );
 }
@@ -0,0 +1,234 @@
+use std::collections::BinaryHeap;
+
+use cgmath::Vector3;
+
+use crate::graph::QueueEntry;
+use crate::terrain::TerrainNoise;
+use crate::uv::longitude_latitude;
+use crate::Polyhedron;
+
+/// Whittaker-style biomes, picked by temperature and moisture.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Ocean,
+    Ice,
+    Tundra,
+    BorealForest,
+    TemperateGrassland,
+    TemperateForest,
+    TemperateRainforest,
+    Desert,
+    Savanna,
+    TropicalRainforest,
+}
+
+impl Biome {
+    pub fn color(self) -> Vector3<f32> {
+        match self {
+            Biome::Ocean => Vector3::new(0.180, 0.322, 0.549),
+            Biome::Ice => Vector3::new(0.925, 0.949, 0.965),
+            Biome::Tundra => Vector3::new(0.600, 0.624, 0.541),
+            Biome::BorealForest => Vector3::new(0.275, 0.427, 0.345),
+            Biome::TemperateGrassland => Vector3::new(0.714, 0.706, 0.427),
+            Biome::TemperateForest => Vector3::new(0.298, 0.549, 0.278),
+            Biome::TemperateRainforest => Vector3::new(0.176, 0.447, 0.310),
+            Biome::Desert => Vector3::new(0.871, 0.757, 0.502),
+            Biome::Savanna => Vector3::new(0.651, 0.659, 0.310),
+            Biome::TropicalRainforest => Vector3::new(0.102, 0.420, 0.165),
+        }
+    }
+}
+
+/// Temperatures (in degrees Celsius) and moisture (from 0 to 1) that separate biomes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BiomeThresholds {
+    /// Below this everything is `Ice`, including the ocean.
+    pub ice_temperature: f32,
+    /// Below this land is `Tundra`.
+    pub tundra_temperature: f32,
+    /// Below this land is `Tundra` when dry and `BorealForest` otherwise.
+    pub temperate_temperature: f32,
+    /// Below this land is temperate, and tropical above.
+    pub tropical_temperature: f32,
+    /// Below this land is `TemperateGrassland` or `Desert`.
+    pub dry_moisture: f32,
+    /// Above this land is `TemperateRainforest` or `TropicalRainforest`.
+    pub wet_moisture: f32,
+}
+
+impl Default for BiomeThresholds {
+    fn default() -> BiomeThresholds {
+        BiomeThresholds {
+            ice_temperature: -10.0,
+            tundra_temperature: -5.0,
+            temperate_temperature: 5.0,
+            tropical_temperature: 20.0,
+            dry_moisture: 0.33,
+            wet_moisture: 0.66,
+        }
+    }
+}
+
+impl BiomeThresholds {
+    pub fn classify(&self, temperature: f32, moisture: f32, ocean: bool) -> Biome {
+        if temperature < self.ice_temperature {
+            Biome::Ice
+        } else if ocean {
+            Biome::Ocean
+        } else if temperature < self.tundra_temperature {
+            Biome::Tundra
+        } else if temperature < self.temperate_temperature {
+            if moisture < self.dry_moisture {
+                Biome::Tundra
+            } else {
+                Biome::BorealForest
+            }
+        } else if temperature < self.tropical_temperature {
+            if moisture < self.dry_moisture {
+                Biome::TemperateGrassland
+            } else if moisture < self.wet_moisture {
+                Biome::TemperateForest
+            } else {
+                Biome::TemperateRainforest
+            }
+        } else if moisture < self.dry_moisture {
+            Biome::Desert
+        } else if moisture < self.wet_moisture {
+            Biome::Savanna
+        } else {
+            Biome::TropicalRainforest
+        }
+    }
+}
+
+/// Settings for `compute_climate`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClimateSettings {
+    /// Faces with an elevation below this are ocean.
+    pub sea_level: f32,
+    pub equator_temperature: f32,
+    pub pole_temperature: f32,
+    /// Degrees the temperature drops per unit of elevation above sea level.
+    pub lapse_rate: f32,
+    /// Distance from the ocean, as an angle from the center in radians, over which the
+    /// moisture blown in from it drops to about a third.
+    pub moisture_falloff: f32,
+    /// Noise added to the moisture of land, scaled by its amplitude.
+    pub moisture_noise: TerrainNoise,
+    pub thresholds: BiomeThresholds,
+}
+
+impl Default for ClimateSettings {
+    fn default() -> ClimateSettings {
+        ClimateSettings {
+            sea_level: 0.0,
+            equator_temperature: 30.0,
+            pole_temperature: -30.0,
+            lapse_rate: 40.0,
+            moisture_falloff: 0.2,
+            moisture_noise: TerrainNoise {
+                seed: 1,
+                frequency: 2.0,
+                amplitude: 0.3,
+                ..TerrainNoise::default()
+            },
+            thresholds: BiomeThresholds::default(),
+        }
+    }
+}
+
+/// Per-face climate.
+#[derive(Debug, Clone)]
+pub struct Climate {
+    /// Degrees Celsius.
+    pub temperatures: Vec<f32>,
+    /// From 0 for completely dry to 1 for the ocean.
+    pub moisture: Vec<f32>,
+    pub biomes: Vec<Biome>,
+}
+
+impl Polyhedron {
+    /// Works out the temperature, moisture and biome of every face from its latitude and
+    /// `face_elevations`, which are all 0 if they haven't been assigned.
+    ///
+    /// Temperature falls from the equator to the poles and with height above sea level.
+    /// Moisture falls with the distance over land to the nearest ocean face, plus some noise.
+    pub fn compute_climate(&self, settings: &ClimateSettings) -> Climate {
+        let face_count = self.faces.len();
+        let elevation =
+            |face_index: usize| self.face_elevations.get(face_index).cloned().unwrap_or(0.0);
+        let centers: Vec<Vector3<f32>> = (0..face_count).map(|i| self.face_center(i)).collect();
+        let ocean: Vec<bool> = (0..face_count)
+            .map(|i| elevation(i) < settings.sea_level)
+            .collect();
+
+        let temperatures: Vec<f32> = (0..face_count)
+            .map(|i| {
+                let (_, latitude) = longitude_latitude(centers[i]);
+                let height = (elevation(i) - settings.sea_level).max(0.0);
+                settings.pole_temperature
+                    + (settings.equator_temperature - settings.pole_temperature) * latitude.cos()
+                    - settings.lapse_rate * height
+            })
+            .collect();
+
+        // distance to the nearest ocean face, found from all ocean faces at once
+        let graph = self.tile_graph();
+        let mut ocean_distances = vec![f32::INFINITY; face_count];
+        let mut open = BinaryHeap::new();
+        for face in (0..face_count).filter(|i| ocean[*i]) {
+            ocean_distances[face] = 0.0;
+            open.push(QueueEntry {
+                estimate: 0.0,
+                face,
+            });
+        }
+        while let Some(QueueEntry { estimate, face }) = open.pop() {
+            if estimate > ocean_distances[face] {
+                continue;
+            }
+            for neighbor in graph.neighbors(face).iter() {
+                let distance = estimate + graph.great_circle_distance(face, *neighbor);
+                if distance < ocean_distances[*neighbor] {
+                    ocean_distances[*neighbor] = distance;
+                    open.push(QueueEntry {
+                        estimate: distance,
+                        face: *neighbor,
+                    });
+                }
+            }
+        }
+
+        let noise = settings.moisture_noise.sample(&centers);
+        let moisture: Vec<f32> = (0..face_count)
+            .map(|i| {
+                if ocean[i] {
+                    return 1.0;
+                }
+                let from_ocean =
+                    (-ocean_distances[i] / settings.moisture_falloff.max(f32::EPSILON)).exp();
+                (from_ocean + noise[i]).clamp(0.0, 1.0)
+            })
+            .collect();
+
+        let biomes = (0..face_count)
+            .map(|i| {
+                settings
+                    .thresholds
+                    .classify(temperatures[i], moisture[i], ocean[i])
+            })
+            .collect();
+
+        Climate {
+            temperatures,
+            moisture,
+            biomes,
+        }
+    }
+
+    /// Colors every face with the color of its biome.
+    pub fn color_faces_by_biome(&mut self, biomes: &[Biome]) {
+        let colors: Vec<Vector3<f32>> = biomes.iter().map(|biome| biome.color()).collect();
+        self.assign_face_colors(&colors);
+    }
+}
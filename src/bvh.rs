@@ -0,0 +1,238 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::Polyhedron;
+
+const MAX_LEAF_SIZE: usize = 4;
+
+/// Where a ray first hits a polyhedron.
+#[derive(Debug, Clone, Copy)]
+pub struct RayHit {
+    /// Index of the triangle in `cells`.
+    pub cell: usize,
+    /// Index of the face the triangle belongs to.
+    pub face: usize,
+    /// Weights of the triangle's `a`, `b` and `c` vertices at the hit point.
+    pub barycentric: Vector3<f32>,
+    /// Distance along the ray from its origin.
+    pub distance: f32,
+    pub point: Vector3<f32>,
+}
+
+#[derive(Debug)]
+struct BvhNode {
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+    /// Index of the first child node for inner nodes, or of the first triangle for leaves.
+    start: usize,
+    /// Number of triangles in a leaf, 0 for inner nodes.
+    count: usize,
+}
+
+/// Bounding volume hierarchy over the triangles of a polyhedron for ray casting. It copies
+/// the positions it is built from, so rebuild it after moving vertices.
+#[derive(Debug)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    cells: Vec<usize>,
+    triangles: Vec<[Vector3<f32>; 3]>,
+    cell_faces: Vec<usize>,
+}
+
+impl Bvh {
+    pub fn new(polyhedron: &Polyhedron) -> Bvh {
+        let triangles: Vec<[Vector3<f32>; 3]> = polyhedron
+            .cells
+            .iter()
+            .map(|cell| {
+                [
+                    polyhedron.positions[cell.a].0,
+                    polyhedron.positions[cell.b].0,
+                    polyhedron.positions[cell.c].0,
+                ]
+            })
+            .collect();
+        let centroids: Vec<Vector3<f32>> = triangles
+            .iter()
+            .map(|[a, b, c]| (a + b + c) / 3.0)
+            .collect();
+        let mut bvh = Bvh {
+            nodes: vec![],
+            cells: (0..triangles.len()).collect(),
+            triangles,
+            cell_faces: polyhedron.cell_face_indices(),
+        };
+        if !bvh.cells.is_empty() {
+            bvh.nodes.push(BvhNode {
+                min: Vector3::new(0.0, 0.0, 0.0),
+                max: Vector3::new(0.0, 0.0, 0.0),
+                start: 0,
+                count: bvh.cells.len(),
+            });
+            bvh.split(0, &centroids);
+        }
+        bvh
+    }
+
+    fn split(&mut self, node_index: usize, centroids: &[Vector3<f32>]) {
+        let start = self.nodes[node_index].start;
+        let count = self.nodes[node_index].count;
+        let mut min = Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for cell in self.cells[start..start + count].iter() {
+            for vertex in self.triangles[*cell].iter() {
+                min = Vector3::new(
+                    min.x.min(vertex.x),
+                    min.y.min(vertex.y),
+                    min.z.min(vertex.z),
+                );
+                max = Vector3::new(
+                    max.x.max(vertex.x),
+                    max.y.max(vertex.y),
+                    max.z.max(vertex.z),
+                );
+            }
+        }
+        self.nodes[node_index].min = min;
+        self.nodes[node_index].max = max;
+        if count <= MAX_LEAF_SIZE {
+            return;
+        }
+
+        let extent = max - min;
+        let axis = if extent.x >= extent.y && extent.x >= extent.z {
+            0
+        } else if extent.y >= extent.z {
+            1
+        } else {
+            2
+        };
+        self.cells[start..start + count].sort_by(|a, b| {
+            centroids[*a][axis]
+                .partial_cmp(&centroids[*b][axis])
+                .unwrap()
+        });
+        let half = count / 2;
+
+        let left = self.nodes.len();
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            start,
+            count: half,
+        });
+        self.nodes.push(BvhNode {
+            min,
+            max,
+            start: start + half,
+            count: count - half,
+        });
+        self.nodes[node_index].start = left;
+        self.nodes[node_index].count = 0;
+        self.split(left, centroids);
+        self.split(left + 1, centroids);
+    }
+
+    /// Closest hit of a ray with any triangle, from either side.
+    pub fn raycast(&self, origin: Vector3<f32>, direction: Vector3<f32>) -> Option<RayHit> {
+        if self.nodes.is_empty() || direction.magnitude2() == 0.0 {
+            return None;
+        }
+        let direction = direction.normalize();
+        let inverse_direction =
+            Vector3::new(1.0 / direction.x, 1.0 / direction.y, 1.0 / direction.z);
+        let mut closest: Option<RayHit> = None;
+        let mut stack = vec![0];
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            let max_distance = closest.map_or(f32::INFINITY, |hit| hit.distance);
+            if !hits_box(origin, inverse_direction, node.min, node.max, max_distance) {
+                continue;
+            }
+            if node.count == 0 {
+                stack.push(node.start);
+                stack.push(node.start + 1);
+                continue;
+            }
+            for cell in self.cells[node.start..node.start + node.count].iter() {
+                if let Some((distance, u, v)) =
+                    intersect_triangle(origin, direction, &self.triangles[*cell])
+                {
+                    if distance < closest.map_or(f32::INFINITY, |hit| hit.distance) {
+                        closest = Some(RayHit {
+                            cell: *cell,
+                            face: self.cell_faces[*cell],
+                            barycentric: Vector3::new(1.0 - u - v, u, v),
+                            distance,
+                            point: origin + direction * distance,
+                        });
+                    }
+                }
+            }
+        }
+        closest
+    }
+}
+
+impl Polyhedron {
+    pub fn bvh(&self) -> Bvh {
+        Bvh::new(self)
+    }
+}
+
+fn hits_box(
+    origin: Vector3<f32>,
+    inverse_direction: Vector3<f32>,
+    min: Vector3<f32>,
+    max: Vector3<f32>,
+    max_distance: f32,
+) -> bool {
+    let mut near = 0.0_f32;
+    let mut far = max_distance;
+    for axis in 0..3 {
+        if inverse_direction[axis].is_infinite() {
+            // parallel to the slab, so it can only be inside it the whole way
+            if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                return false;
+            }
+            continue;
+        }
+        let t1 = (min[axis] - origin[axis]) * inverse_direction[axis];
+        let t2 = (max[axis] - origin[axis]) * inverse_direction[axis];
+        near = near.max(t1.min(t2));
+        far = far.min(t1.max(t2));
+    }
+    near <= far
+}
+
+/// Möller–Trumbore ray triangle intersection, returning the distance and the weights of the
+/// second and third vertices.
+fn intersect_triangle(
+    origin: Vector3<f32>,
+    direction: Vector3<f32>,
+    [a, b, c]: &[Vector3<f32>; 3],
+) -> Option<(f32, f32, f32)> {
+    let e1 = b - a;
+    let e2 = c - a;
+    let p = direction.cross(e2);
+    let determinant = e1.dot(p);
+    if determinant.abs() < f32::EPSILON * e1.magnitude() * e2.magnitude() {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+    let s = origin - a;
+    let u = s.dot(p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+    let q = s.cross(e1);
+    let v = direction.dot(q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    let distance = e2.dot(q) * inverse_determinant;
+    if distance < 0.0 {
+        return None;
+    }
+    Some((distance, u, v))
+}
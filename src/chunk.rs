@@ -0,0 +1,89 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+use serde::Serialize;
+
+use crate::lattice::{lattice_cell_index, BaseLattice};
+use crate::{ArraySerializedVector, Polyhedron};
+
+/// Axis aligned bounding box and bounding sphere of a set of points.
+#[derive(Serialize, Debug)]
+pub struct Bounds {
+    pub min: ArraySerializedVector,
+    pub max: ArraySerializedVector,
+    pub center: ArraySerializedVector,
+    pub radius: f32,
+}
+
+impl Bounds {
+    pub fn from_points(points: &[ArraySerializedVector]) -> Bounds {
+        let mut min = Vector3::new(f32::INFINITY, f32::INFINITY, f32::INFINITY);
+        let mut max = Vector3::new(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for point in points {
+            min = Vector3::new(
+                min.x.min(point.0.x),
+                min.y.min(point.0.y),
+                min.z.min(point.0.z),
+            );
+            max = Vector3::new(
+                max.x.max(point.0.x),
+                max.y.max(point.0.y),
+                max.z.max(point.0.z),
+            );
+        }
+        let center = min.lerp(max, 0.5);
+        let radius = points
+            .iter()
+            .map(|point| (point.0 - center).magnitude())
+            .fold(0.0, f32::max);
+        Bounds {
+            min: ArraySerializedVector(min),
+            max: ArraySerializedVector(max),
+            center: ArraySerializedVector(center),
+            radius,
+        }
+    }
+}
+
+/// A spatial piece of a polyhedron with its own vertex and index buffers.
+#[derive(Debug)]
+pub struct Chunk {
+    /// Index of the triangle of `new_isocahedron(_, level)` this chunk covers.
+    pub id: usize,
+    pub polyhedron: Polyhedron,
+    /// For every face of the chunk, the index of the face in the original polyhedron.
+    pub faces: Vec<usize>,
+    pub bounds: Bounds,
+}
+
+impl Polyhedron {
+    /// Splits the polyhedron into chunks along the triangles of `new_isocahedron(_, level)`,
+    /// so level 0 gives a chunk per base icosahedron face and every level above splits each
+    /// chunk into 4. Faces belong to the chunk their center falls in, and empty chunks are
+    /// left out.
+    pub fn chunks(&self, level: u32) -> Vec<Chunk> {
+        let cols = 2usize.pow(level);
+        let lattice = BaseLattice::new();
+        let mut chunk_faces: Vec<Vec<usize>> = vec![vec![]; 20 * cols * cols];
+        for face_index in 0..self.faces.len() {
+            let (base, weights) = lattice.locate(self.face_center(face_index));
+            let (i, k, odd) = lattice.cell(weights, cols);
+            chunk_faces[lattice_cell_index(base, i, k, odd, cols)].push(face_index);
+        }
+
+        chunk_faces
+            .into_iter()
+            .enumerate()
+            .filter(|(_, faces)| !faces.is_empty())
+            .map(|(id, faces)| {
+                let polyhedron = self.extract_faces(&faces);
+                let bounds = Bounds::from_points(&polyhedron.positions);
+                Chunk {
+                    id,
+                    polyhedron,
+                    faces,
+                    bounds,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,79 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+const VIRIDIS: [(f32, f32, f32); 9] = [
+    (0.267, 0.005, 0.329),
+    (0.278, 0.176, 0.482),
+    (0.231, 0.322, 0.545),
+    (0.173, 0.447, 0.557),
+    (0.129, 0.569, 0.549),
+    (0.157, 0.682, 0.502),
+    (0.369, 0.788, 0.384),
+    (0.678, 0.863, 0.188),
+    (0.992, 0.906, 0.145),
+];
+
+const MAGMA: [(f32, f32, f32); 9] = [
+    (0.001, 0.000, 0.014),
+    (0.110, 0.063, 0.267),
+    (0.310, 0.071, 0.482),
+    (0.506, 0.145, 0.506),
+    (0.710, 0.212, 0.478),
+    (0.898, 0.314, 0.392),
+    (0.984, 0.529, 0.380),
+    (0.996, 0.761, 0.529),
+    (0.988, 0.992, 0.749),
+];
+
+/// Maps a value in `0.0..=1.0` to an RGB color.
+#[derive(Debug, Clone)]
+pub enum Colormap {
+    Viridis,
+    Magma,
+    Grayscale,
+    /// Color stops as `(position, color)` pairs, sorted by position.
+    Custom(Vec<(f32, Vector3<f32>)>),
+}
+
+impl Colormap {
+    pub fn sample(&self, t: f32) -> Vector3<f32> {
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        match self {
+            Colormap::Viridis => sample_even_stops(&VIRIDIS, t),
+            Colormap::Magma => sample_even_stops(&MAGMA, t),
+            Colormap::Grayscale => Vector3::new(t, t, t),
+            Colormap::Custom(stops) => sample_stops(stops, t),
+        }
+    }
+}
+
+fn sample_even_stops(stops: &[(f32, f32, f32)], t: f32) -> Vector3<f32> {
+    let scaled = t * (stops.len() - 1) as f32;
+    let lower = (scaled.floor() as usize).min(stops.len() - 2);
+    let (r0, g0, b0) = stops[lower];
+    let (r1, g1, b1) = stops[lower + 1];
+    Vector3::new(r0, g0, b0).lerp(Vector3::new(r1, g1, b1), scaled - lower as f32)
+}
+
+fn sample_stops(stops: &[(f32, Vector3<f32>)], t: f32) -> Vector3<f32> {
+    match stops.len() {
+        0 => Vector3::new(1.0, 1.0, 1.0),
+        1 => stops[0].1,
+        _ => {
+            if t <= stops[0].0 {
+                return stops[0].1;
+            }
+            for pair in stops.windows(2) {
+                let (start, start_color) = pair[0];
+                let (end, end_color) = pair[1];
+                if t <= end {
+                    if end <= start {
+                        return end_color;
+                    }
+                    return start_color.lerp(end_color, (t - start) / (end - start));
+                }
+            }
+            stops[stops.len() - 1].1
+        }
+    }
+}
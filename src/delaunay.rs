@@ -0,0 +1,270 @@
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::{sort_around, ArraySerializedVector, Polyhedron, Triangle, Triangulation};
+
+/// Steps a point location walk can take before falling back to checking every triangle.
+const MAX_WALK_STEPS: usize = 10000;
+
+impl Polyhedron {
+    /// Delaunay triangulation of points on a sphere, which is their convex hull. The points
+    /// are scaled onto the sphere of `radius` and keep their order as vertices, and every
+    /// triangle is a face.
+    ///
+    /// Points that aren't on the hull, such as duplicates, are left out of the triangles.
+    /// Returns an empty polyhedron if the points all lie in one plane.
+    pub fn new_spherical_delaunay(radius: f32, points: &[Vector3<f32>]) -> Polyhedron {
+        let directions: Vec<Vector3<f64>> = points
+            .iter()
+            .map(|point| point.cast::<f64>().unwrap().normalize())
+            .collect();
+        let mut delaunay = Polyhedron::new();
+        for direction in directions.iter() {
+            delaunay.positions.push(ArraySerializedVector(
+                direction.cast::<f32>().unwrap() * radius,
+            ));
+            delaunay
+                .normals
+                .push(ArraySerializedVector(Vector3::new(0.0, 0.0, 0.0)));
+            delaunay
+                .colors
+                .push(ArraySerializedVector(Vector3::new(1.0, 1.0, 1.0)));
+        }
+        if let Some(hull) = SphereHull::new(&directions) {
+            for [a, b, c] in hull.triangles() {
+                delaunay.cells.push(Triangle::new(a, b, c));
+            }
+        }
+        delaunay.triangles_to_faces();
+        delaunay
+    }
+
+    /// Voronoi diagram of points on a sphere, with one face around every point that is on
+    /// their convex hull, in the order of the points. The corners of the faces are the
+    /// centers of the circles around the triangles of `new_spherical_delaunay`, and the faces
+    /// are split into triangles like the faces of `new_truncated_isocahedron_with_triangulation`
+    /// around the points themselves.
+    pub fn new_spherical_voronoi(
+        radius: f32,
+        points: &[Vector3<f32>],
+        triangulation: Triangulation,
+    ) -> Polyhedron {
+        let delaunay = Polyhedron::new_spherical_delaunay(radius, points);
+        let mut point_corners: Vec<Vec<Vector3<f32>>> = vec![vec![]; points.len()];
+        for cell in delaunay.cells.iter() {
+            let a = delaunay.positions[cell.a].0;
+            let b = delaunay.positions[cell.b].0;
+            let c = delaunay.positions[cell.c].0;
+            let circumcenter = (b - a).cross(c - a).normalize() * radius;
+            for vertex in [cell.a, cell.b, cell.c].iter() {
+                point_corners[*vertex].push(circumcenter);
+            }
+        }
+
+        let mut voronoi = Polyhedron::new();
+        for (i, corners) in point_corners.into_iter().enumerate() {
+            if corners.len() < 3 {
+                continue;
+            }
+            let center = delaunay.positions[i].0;
+            let corners = sort_around(center, center, corners);
+            voronoi.add_face_polygon(center, &corners, triangulation);
+        }
+        voronoi
+    }
+}
+
+/// Incremental convex hull of unit vectors, which stays a triangulation of the sphere as
+/// every point is added.
+struct SphereHull {
+    triangles: Vec<[usize; 3]>,
+    removed: Vec<bool>,
+    /// The triangle on the left of every directed edge.
+    edges: HashMap<(usize, usize), usize>,
+}
+
+impl SphereHull {
+    fn new(points: &[Vector3<f64>]) -> Option<SphereHull> {
+        let start = initial_tetrahedron(points)?;
+        let mut hull = SphereHull {
+            triangles: vec![],
+            removed: vec![],
+            edges: HashMap::new(),
+        };
+        let [a, b, c, d] = start;
+        // orient the first triangle so the fourth point is behind it
+        let (b, c) = if (points[b] - points[a])
+            .cross(points[c] - points[a])
+            .dot(points[d] - points[a])
+            > 0.0
+        {
+            (c, b)
+        } else {
+            (b, c)
+        };
+        hull.add_triangle([a, b, c]);
+        hull.add_triangle([a, d, b]);
+        hull.add_triangle([b, d, c]);
+        hull.add_triangle([c, d, a]);
+
+        // inserting nearby points one after another keeps the walks to them short
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by_cached_key(|i| morton_code(points[*i]));
+        let mut last = 0;
+        for point_index in order {
+            if start.contains(&point_index) {
+                continue;
+            }
+            if let Some(visible) = hull.find_visible(points, points[point_index], last) {
+                last = hull.insert(points, point_index, visible);
+            }
+        }
+        Some(hull)
+    }
+
+    fn triangles(&self) -> Vec<[usize; 3]> {
+        self.triangles
+            .iter()
+            .zip(self.removed.iter())
+            .filter(|(_, removed)| !**removed)
+            .map(|(triangle, _)| *triangle)
+            .collect()
+    }
+
+    fn add_triangle(&mut self, triangle: [usize; 3]) -> usize {
+        let index = self.triangles.len();
+        for i in 0..3 {
+            self.edges
+                .insert((triangle[i], triangle[(i + 1) % 3]), index);
+        }
+        self.triangles.push(triangle);
+        self.removed.push(false);
+        index
+    }
+
+    fn is_visible(&self, points: &[Vector3<f64>], triangle: usize, point: Vector3<f64>) -> bool {
+        let [a, b, c] = self.triangles[triangle];
+        let (a, b, c) = (points[a], points[b], points[c]);
+        let normal = (b - a).cross(c - a);
+        normal.dot(point - a) > 1e-12 * normal.magnitude()
+    }
+
+    /// Walks across the hull toward the triangle the point's direction passes through, which
+    /// the point can always see unless it's already inside the hull.
+    fn find_visible(
+        &self,
+        points: &[Vector3<f64>],
+        point: Vector3<f64>,
+        start: usize,
+    ) -> Option<usize> {
+        let mut current = start;
+        'walk: for _ in 0..MAX_WALK_STEPS {
+            let triangle = self.triangles[current];
+            for i in 0..3 {
+                let (from, to) = (triangle[i], triangle[(i + 1) % 3]);
+                if point.dot(points[from].cross(points[to])) < 0.0 {
+                    current = self.edges[&(to, from)];
+                    continue 'walk;
+                }
+            }
+            if self.is_visible(points, current, point) {
+                return Some(current);
+            }
+            break;
+        }
+        (0..self.triangles.len())
+            .find(|triangle| !self.removed[*triangle] && self.is_visible(points, *triangle, point))
+    }
+
+    /// Replaces the triangles the point can see with a fan of triangles from the point to
+    /// the edge of the visible region, returning one of the new triangles.
+    fn insert(&mut self, points: &[Vector3<f64>], point_index: usize, visible: usize) -> usize {
+        let point = points[point_index];
+        let mut region = vec![visible];
+        let mut in_region: HashMap<usize, bool> = HashMap::new();
+        in_region.insert(visible, true);
+        let mut i = 0;
+        while i < region.len() {
+            let triangle = self.triangles[region[i]];
+            for k in 0..3 {
+                let neighbor = self.edges[&(triangle[(k + 1) % 3], triangle[k])];
+                if let Entry::Vacant(entry) = in_region.entry(neighbor) {
+                    let neighbor_visible = self.is_visible(points, neighbor, point);
+                    entry.insert(neighbor_visible);
+                    if neighbor_visible {
+                        region.push(neighbor);
+                    }
+                }
+            }
+            i += 1;
+        }
+
+        let mut horizon = vec![];
+        for triangle_index in region.iter() {
+            let triangle = self.triangles[*triangle_index];
+            for k in 0..3 {
+                let edge = (triangle[k], triangle[(k + 1) % 3]);
+                if !in_region[&self.edges[&(edge.1, edge.0)]] {
+                    horizon.push(edge);
+                }
+            }
+        }
+        for triangle_index in region {
+            self.removed[triangle_index] = true;
+            let triangle = self.triangles[triangle_index];
+            for k in 0..3 {
+                let edge = (triangle[k], triangle[(k + 1) % 3]);
+                if self.edges.get(&edge) == Some(&triangle_index) {
+                    self.edges.remove(&edge);
+                }
+            }
+        }
+        let mut last = 0;
+        for (from, to) in horizon {
+            last = self.add_triangle([from, to, point_index]);
+        }
+        last
+    }
+}
+
+/// Position of a unit vector along a Z-order curve through the cube around the sphere.
+fn morton_code(point: Vector3<f64>) -> u64 {
+    let quantize = |value: f64| (((value + 1.0) / 2.0).clamp(0.0, 1.0) * 1023.0) as u64;
+    let (x, y, z) = (quantize(point.x), quantize(point.y), quantize(point.z));
+    let mut code = 0;
+    for bit in 0..10 {
+        code |= ((x >> bit) & 1) << (3 * bit)
+            | ((y >> bit) & 1) << (3 * bit + 1)
+            | ((z >> bit) & 1) << (3 * bit + 2);
+    }
+    code
+}
+
+/// Four points that aren't in one plane, to start the hull from.
+fn initial_tetrahedron(points: &[Vector3<f64>]) -> Option<[usize; 4]> {
+    let a = 0;
+    let b = (0..points.len()).max_by(|i, j| {
+        let distance = |k: &usize| (points[*k] - points[a]).magnitude2();
+        distance(i).partial_cmp(&distance(j)).unwrap()
+    })?;
+    let line = points[b] - points[a];
+    let c = (0..points.len()).max_by(|i, j| {
+        let distance = |k: &usize| line.cross(points[*k] - points[a]).magnitude2();
+        distance(i).partial_cmp(&distance(j)).unwrap()
+    })?;
+    let normal = line.cross(points[c] - points[a]);
+    if normal.magnitude2() < 1e-20 {
+        return None;
+    }
+    let d = (0..points.len()).max_by(|i, j| {
+        let distance = |k: &usize| normal.dot(points[*k] - points[a]).abs();
+        distance(i).partial_cmp(&distance(j)).unwrap()
+    })?;
+    if normal.dot(points[d] - points[a]).abs() < 1e-10 * normal.magnitude() {
+        return None;
+    }
+    Some([a, b, c, d])
+}
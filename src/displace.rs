@@ -0,0 +1,75 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::{NormalMode, Polyhedron};
+
+impl Polyhedron {
+    /// Moves every vertex away from the center of the polyhedron by its height, or toward it
+    /// for negative heights, then recomputes the normals with the given mode. Panics unless
+    /// there is one height per vertex.
+    pub fn displace_vertices(&mut self, heights: &[f32], normals: NormalMode) {
+        assert_eq!(
+            heights.len(),
+            self.positions.len(),
+            "Expected one height per vertex"
+        );
+        for (position, height) in self.positions.iter_mut().zip(heights.iter()) {
+            let distance = position.0.magnitude();
+            if distance > 0.0 {
+                position.0 *= (distance + height) / distance;
+            }
+        }
+        // cached positions no longer match the vertices they point to
+        self.added_vert_cache.clear();
+        self.compute_normals(normals);
+    }
+
+    /// Like `displace_vertices`, with the height of each vertex given by a function of the
+    /// direction from the center to the vertex.
+    pub fn displace_vertices_with<F>(&mut self, height: F, normals: NormalMode)
+    where
+        F: Fn(Vector3<f32>) -> f32,
+    {
+        let heights: Vec<f32> = self
+            .positions
+            .iter()
+            .map(|position| height(position.0.normalize()))
+            .collect();
+        self.displace_vertices(&heights, normals);
+    }
+
+    /// Like `displace_vertices`, raising each face by its height. Vertices shared by several
+    /// faces are raised by the average of their heights, so use `unique_face_vertices` first
+    /// to move whole tiles independently of their neighbors. Panics unless there is one
+    /// height per face.
+    pub fn displace_faces(&mut self, heights: &[f32], normals: NormalMode) {
+        assert_eq!(
+            heights.len(),
+            self.faces.len(),
+            "Expected one height per face"
+        );
+        let mut sums = vec![0.0; self.positions.len()];
+        let mut counts = vec![0; self.positions.len()];
+        for (face, height) in self.faces.iter().zip(heights.iter()) {
+            let mut vertices: Vec<usize> = face
+                .iter()
+                .flat_map(|cell_index| {
+                    let cell = &self.cells[*cell_index];
+                    vec![cell.a, cell.b, cell.c]
+                })
+                .collect();
+            vertices.sort_unstable();
+            vertices.dedup();
+            for vertex in vertices {
+                sums[vertex] += height;
+                counts[vertex] += 1;
+            }
+        }
+        let vertex_heights: Vec<f32> = sums
+            .iter()
+            .zip(counts.iter())
+            .map(|(sum, count)| if *count > 0 { sum / *count as f32 } else { 0.0 })
+            .collect();
+        self.displace_vertices(&vertex_heights, normals);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::{vertex_key, ArraySerializedVector, Polyhedron, Triangle, VertexKey};
+
+impl Polyhedron {
+    /// Builds a flat-topped prism out of every face, with its top cap raised by the face's
+    /// height like `displace_faces` but without leaning into its neighbors.
+    ///
+    /// Without a `bottom`, side walls only go down to the top of lower neighbors (or to the
+    /// original surface at the edge of the polyhedron), making one stepped surface. With a
+    /// `bottom` height, every prism is closed on its own with full walls and a bottom cap.
+    ///
+    /// Face `i` of the result is the prism of face `i`, and every cap and wall gets its own
+    /// vertices with flat normals. Panics unless there is one height per face.
+    pub fn extruded(&self, heights: &[f32], bottom: Option<f32>) -> Polyhedron {
+        assert_eq!(
+            heights.len(),
+            self.faces.len(),
+            "Expected one height per face"
+        );
+        let boundaries: Vec<Vec<usize>> = (0..self.faces.len())
+            .map(|face_index| self.face_boundary(face_index))
+            .collect();
+        let mut edge_faces: HashMap<(VertexKey, VertexKey), usize> = HashMap::new();
+        for (face_index, boundary) in boundaries.iter().enumerate() {
+            for (i, vertex) in boundary.iter().enumerate() {
+                let next = boundary[(i + 1) % boundary.len()];
+                edge_faces.insert(
+                    (
+                        vertex_key(self.positions[*vertex].0),
+                        vertex_key(self.positions[next].0),
+                    ),
+                    face_index,
+                );
+            }
+        }
+
+        let mut extruded = Polyhedron::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            let height = heights[face_index];
+            let mut new_face = vec![];
+
+            let top_normal = self.face_normal(face_index);
+            let mut top_vertices: HashMap<usize, usize> = HashMap::new();
+            for cell_index in face.iter() {
+                let cell = &self.cells[*cell_index];
+                let mut new_indices = [0; 3];
+                for (new_index, old_index) in
+                    new_indices.iter_mut().zip([cell.a, cell.b, cell.c].iter())
+                {
+                    *new_index = *top_vertices.entry(*old_index).or_insert_with(|| {
+                        extruded.push_vertex(
+                            self.raised(*old_index, height),
+                            top_normal,
+                            self.vertex_color(*old_index),
+                        )
+                    });
+                }
+                extruded.push_facing(new_indices, top_normal);
+                new_face.push(extruded.cells.len() - 1);
+            }
+
+            let boundary = &boundaries[face_index];
+            for (i, start) in boundary.iter().enumerate() {
+                let end = boundary[(i + 1) % boundary.len()];
+                let low = match bottom {
+                    Some(bottom) => bottom,
+                    None => edge_faces
+                        .get(&(
+                            vertex_key(self.positions[end].0),
+                            vertex_key(self.positions[*start].0),
+                        ))
+                        .map_or(0.0, |neighbor| heights[*neighbor]),
+                };
+                if low >= height {
+                    continue;
+                }
+                let top_start = self.raised(*start, height);
+                let top_end = self.raised(end, height);
+                let bottom_start = self.raised(*start, low);
+                let bottom_end = self.raised(end, low);
+                let normal = (bottom_start - top_start)
+                    .cross(bottom_end - top_start)
+                    .normalize();
+                let wall: Vec<usize> = [
+                    (top_start, *start),
+                    (bottom_start, *start),
+                    (bottom_end, end),
+                    (top_end, end),
+                ]
+                .iter()
+                .map(|(position, old_index)| {
+                    extruded.push_vertex(*position, normal, self.vertex_color(*old_index))
+                })
+                .collect();
+                extruded
+                    .cells
+                    .push(Triangle::new(wall[0], wall[1], wall[2]));
+                new_face.push(extruded.cells.len() - 1);
+                extruded
+                    .cells
+                    .push(Triangle::new(wall[0], wall[2], wall[3]));
+                new_face.push(extruded.cells.len() - 1);
+            }
+
+            if let Some(bottom) = bottom {
+                let mut bottom_vertices: HashMap<usize, usize> = HashMap::new();
+                for cell_index in face.iter() {
+                    let cell = &self.cells[*cell_index];
+                    let mut new_indices = [0; 3];
+                    for (new_index, old_index) in
+                        new_indices.iter_mut().zip([cell.a, cell.b, cell.c].iter())
+                    {
+                        *new_index = *bottom_vertices.entry(*old_index).or_insert_with(|| {
+                            extruded.push_vertex(
+                                self.raised(*old_index, bottom),
+                                -top_normal,
+                                self.vertex_color(*old_index),
+                            )
+                        });
+                    }
+                    extruded.push_facing(new_indices, -top_normal);
+                    new_face.push(extruded.cells.len() - 1);
+                }
+            }
+
+            extruded.faces.push(new_face);
+        }
+        extruded.face_colors = self
+            .face_colors
+            .iter()
+            .map(|color| ArraySerializedVector(color.0))
+            .collect();
+        extruded.face_elevations = self.face_elevations.clone();
+        extruded
+    }
+
+    /// Position of a vertex moved away from the center by a height.
+    pub(crate) fn raised(&self, vertex_index: usize, height: f32) -> Vector3<f32> {
+        let position = self.positions[vertex_index].0;
+        let distance = position.magnitude();
+        if distance > 0.0 {
+            position * (distance + height) / distance
+        } else {
+            position
+        }
+    }
+
+    pub(crate) fn vertex_color(&self, vertex_index: usize) -> Vector3<f32> {
+        self.colors
+            .get(vertex_index)
+            .map_or(Vector3::new(1.0, 1.0, 1.0), |color| color.0)
+    }
+
+    /// Adds a triangle, wound so that it faces the given direction.
+    pub(crate) fn push_facing(&mut self, vertices: [usize; 3], direction: Vector3<f32>) {
+        let [a, b, c] = vertices;
+        let normal = (self.positions[b].0 - self.positions[a].0)
+            .cross(self.positions[c].0 - self.positions[a].0);
+        if normal.dot(direction) < 0.0 {
+            self.cells.push(Triangle::new(a, c, b));
+        } else {
+            self.cells.push(Triangle::new(a, b, c));
+        }
+    }
+
+    pub(crate) fn push_vertex(
+        &mut self,
+        position: Vector3<f32>,
+        normal: Vector3<f32>,
+        color: Vector3<f32>,
+    ) -> usize {
+        self.positions.push(ArraySerializedVector(position));
+        self.normals.push(ArraySerializedVector(normal));
+        self.colors.push(ArraySerializedVector(color));
+        self.positions.len() - 1
+    }
+}
@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, VecDeque};
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::Polyhedron;
+
+/// The faces of a polyhedron and the edges between them, for finding paths across tiles.
+#[derive(Debug, Clone)]
+pub struct TileGraph {
+    centers: Vec<Vector3<f32>>,
+    adjacency: Vec<Vec<usize>>,
+    max_step_angle: f32,
+}
+
+/// Faces from the start to the goal of a path, both included, and the sum of the costs of
+/// its steps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TilePath {
+    pub faces: Vec<usize>,
+    pub cost: f32,
+}
+
+/// Entry of the open set, ordered so the `BinaryHeap` pops the lowest estimate first.
+#[derive(PartialEq)]
+pub(crate) struct QueueEntry {
+    pub estimate: f32,
+    pub face: usize,
+}
+
+impl Eq for QueueEntry {}
+
+impl Ord for QueueEntry {
+    fn cmp(&self, other: &QueueEntry) -> Ordering {
+        other
+            .estimate
+            .partial_cmp(&self.estimate)
+            .unwrap_or(Ordering::Equal)
+            .then_with(|| other.face.cmp(&self.face))
+    }
+}
+
+impl PartialOrd for QueueEntry {
+    fn partial_cmp(&self, other: &QueueEntry) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl TileGraph {
+    pub fn new(polyhedron: &Polyhedron) -> TileGraph {
+        let centers: Vec<Vector3<f32>> = (0..polyhedron.face_count())
+            .map(|i| polyhedron.face_center(i).normalize())
+            .collect();
+        let adjacency = polyhedron.face_adjacency();
+        let mut max_step_angle = 0.0_f32;
+        for (face, neighbors) in adjacency.iter().enumerate() {
+            for neighbor in neighbors.iter() {
+                max_step_angle = max_step_angle.max(angle(centers[face], centers[*neighbor]));
+            }
+        }
+        TileGraph {
+            centers,
+            adjacency,
+            max_step_angle,
+        }
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.centers.len()
+    }
+
+    pub fn neighbors(&self, face: usize) -> &[usize] {
+        &self.adjacency[face]
+    }
+
+    /// Angle between the centers of two faces seen from the center of the polyhedron, which
+    /// is the great-circle distance between them on a sphere of radius 1.
+    pub fn great_circle_distance(&self, a: usize, b: usize) -> f32 {
+        angle(self.centers[a], self.centers[b])
+    }
+
+    /// Fewest steps between neighbors it takes to get from one face to another, or `None` if
+    /// they aren't connected.
+    pub fn hop_distance(&self, a: usize, b: usize) -> Option<usize> {
+        let mut distance = None;
+        self.breadth_first(a, usize::MAX, |face, hops| {
+            if face == b {
+                distance = Some(hops);
+                return false;
+            }
+            true
+        });
+        distance
+    }
+
+    /// Faces at most `k` steps away from a face, including the face itself, in order of
+    /// distance.
+    pub fn tiles_within(&self, face: usize, k: usize) -> Vec<usize> {
+        let mut tiles = vec![];
+        self.breadth_first(face, k, |tile, _| {
+            tiles.push(tile);
+            true
+        });
+        tiles
+    }
+
+    /// Faces exactly `k` steps away from a face. Rings around pentagons have 5 faces for
+    /// every step instead of 6.
+    pub fn ring(&self, face: usize, k: usize) -> Vec<usize> {
+        let mut tiles = vec![];
+        self.breadth_first(face, k, |tile, hops| {
+            if hops == k {
+                tiles.push(tile);
+            }
+            true
+        });
+        tiles
+    }
+
+    /// Visits faces up to `max_hops` steps away in order of distance until `visit` returns
+    /// false.
+    fn breadth_first<F>(&self, start: usize, max_hops: usize, mut visit: F)
+    where
+        F: FnMut(usize, usize) -> bool,
+    {
+        let mut visited = vec![false; self.centers.len()];
+        let mut queue = VecDeque::new();
+        visited[start] = true;
+        queue.push_back((start, 0));
+        while let Some((face, hops)) = queue.pop_front() {
+            if !visit(face, hops) {
+                return;
+            }
+            if hops == max_hops {
+                continue;
+            }
+            for neighbor in self.adjacency[face].iter() {
+                if !visited[*neighbor] {
+                    visited[*neighbor] = true;
+                    queue.push_back((*neighbor, hops + 1));
+                }
+            }
+        }
+    }
+
+    /// Cheapest path between two faces. `cost` gives the cost of stepping from a face to a
+    /// neighbor, or `None` if the step isn't allowed. Costs must not be negative.
+    pub fn dijkstra<F>(&self, start: usize, goal: usize, cost: F) -> Option<TilePath>
+    where
+        F: FnMut(usize, usize) -> Option<f32>,
+    {
+        self.search(start, goal, cost, |_| 0.0)
+    }
+
+    /// Cheapest path between two faces like `dijkstra`, guided by the great-circle distance
+    /// to the goal. `min_step_cost` must be at most the cost of any allowed step for the
+    /// path to be the cheapest one, and the closer it is the fewer faces are searched.
+    pub fn a_star<F>(
+        &self,
+        start: usize,
+        goal: usize,
+        min_step_cost: f32,
+        cost: F,
+    ) -> Option<TilePath>
+    where
+        F: FnMut(usize, usize) -> Option<f32>,
+    {
+        let goal_center = self.centers[goal];
+        // no step covers more than max_step_angle, so this never overestimates the steps left
+        let cost_per_angle = if self.max_step_angle > 0.0 {
+            min_step_cost.max(0.0) / self.max_step_angle
+        } else {
+            0.0
+        };
+        self.search(start, goal, cost, |face| {
+            angle(self.centers[face], goal_center) * cost_per_angle
+        })
+    }
+
+    fn search<F, H>(&self, start: usize, goal: usize, mut cost: F, heuristic: H) -> Option<TilePath>
+    where
+        F: FnMut(usize, usize) -> Option<f32>,
+        H: Fn(usize) -> f32,
+    {
+        let mut costs: Vec<f32> = vec![f32::INFINITY; self.centers.len()];
+        let mut previous: Vec<Option<usize>> = vec![None; self.centers.len()];
+        let mut closed = vec![false; self.centers.len()];
+        let mut open = BinaryHeap::new();
+        costs[start] = 0.0;
+        open.push(QueueEntry {
+            estimate: heuristic(start),
+            face: start,
+        });
+
+        while let Some(QueueEntry { face, .. }) = open.pop() {
+            if closed[face] {
+                continue;
+            }
+            if face == goal {
+                let mut faces = vec![goal];
+                while let Some(previous_face) = previous[faces[faces.len() - 1]] {
+                    faces.push(previous_face);
+                }
+                faces.reverse();
+                return Some(TilePath {
+                    faces,
+                    cost: costs[goal],
+                });
+            }
+            closed[face] = true;
+
+            for neighbor in self.adjacency[face].iter() {
+                if closed[*neighbor] {
+                    continue;
+                }
+                if let Some(step_cost) = cost(face, *neighbor) {
+                    let neighbor_cost = costs[face] + step_cost;
+                    if neighbor_cost < costs[*neighbor] {
+                        costs[*neighbor] = neighbor_cost;
+                        previous[*neighbor] = Some(face);
+                        open.push(QueueEntry {
+                            estimate: neighbor_cost + heuristic(*neighbor),
+                            face: *neighbor,
+                        });
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl Polyhedron {
+    pub fn tile_graph(&self) -> TileGraph {
+        TileGraph::new(self)
+    }
+}
+
+/// Angle between two unit vectors, which is their great-circle distance on the unit sphere.
+fn angle(a: Vector3<f32>, b: Vector3<f32>) -> f32 {
+    a.cross(b).magnitude().atan2(a.dot(b))
+}
@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+
+use crate::lattice::{BaseLattice, LATTICE_TOLERANCE};
+use crate::Polyhedron;
+
+/// Steps between neighboring tiles in axial coordinates.
+const AXIAL_DIRECTIONS: [(i32, i32); 6] = [(1, 0), (0, 1), (-1, 1), (-1, 0), (0, -1), (1, -1)];
+
+/// Position of a tile on a hexsphere: a triangle of the base icosahedron and axial
+/// coordinates in the lattice it was subdivided into.
+///
+/// `q` counts steps from the triangle's first corner toward its second and `r` toward its
+/// third, so on a hexsphere with `n` subdivisions per edge the tiles of a triangle have
+/// `q >= 0`, `r >= 0` and `q + r <= n`. Tiles on the edges and corners shared by several
+/// triangles belong to the one with the lowest index. The 12 pentagons are the corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TileCoord {
+    pub base: usize,
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Converts between the faces of a hexsphere and their `TileCoord`s, and does axial
+/// coordinate math across the triangles of the base icosahedron.
+///
+/// Coordinates outside their triangle are carried into the neighboring triangles as if they
+/// were unfolded flat around the shared edge. A pentagon only has five triangles around it,
+/// so two of the six axial directions from it lead to the same tile.
+#[derive(Debug)]
+pub struct HexGrid {
+    size: i32,
+    base_cells: Vec<[usize; 3]>,
+    coords: Vec<TileCoord>,
+    faces: HashMap<TileCoord, usize>,
+}
+
+impl HexGrid {
+    /// Panics if the polyhedron isn't a hexsphere made by `new_truncated_isocahedron`, which
+    /// is checked by every face center lying on its own lattice point.
+    pub fn new(polyhedron: &Polyhedron) -> HexGrid {
+        let face_count = polyhedron.faces.len();
+        let size = (face_count.saturating_sub(2) as f64 / 10.0).sqrt().round() as usize;
+        assert!(
+            size > 0 && 10 * size * size + 2 == face_count,
+            "Polyhedron isn't a hexsphere"
+        );
+        let base_cells = Polyhedron::base_isocahedron()
+            .cells
+            .iter()
+            .map(|cell| [cell.a, cell.b, cell.c])
+            .collect();
+        let mut grid = HexGrid {
+            size: size as i32,
+            base_cells,
+            coords: Vec::with_capacity(face_count),
+            faces: HashMap::new(),
+        };
+
+        let lattice = BaseLattice::new();
+        for face_index in 0..face_count {
+            let (base, weights) = lattice.locate(polyhedron.face_center(face_index));
+            let q = weights.y * size as f32;
+            let r = weights.z * size as f32;
+            assert!(
+                (q - q.round()).abs() <= LATTICE_TOLERANCE
+                    && (r - r.round()).abs() <= LATTICE_TOLERANCE,
+                "Polyhedron isn't a hexsphere: face {} is off the lattice",
+                face_index
+            );
+            let coord = grid.normalize(TileCoord {
+                base,
+                q: q.round() as i32,
+                r: r.round() as i32,
+            });
+            grid.coords.push(coord);
+            assert!(
+                grid.faces.insert(coord, face_index).is_none(),
+                "Polyhedron isn't a hexsphere: faces share the coordinate {:?}",
+                coord
+            );
+        }
+        grid
+    }
+
+    /// Subdivisions along each edge of the base icosahedron.
+    pub fn size(&self) -> i32 {
+        self.size
+    }
+
+    pub fn coord(&self, face_index: usize) -> TileCoord {
+        self.coords[face_index]
+    }
+
+    /// The face at a coordinate, which doesn't need to be normalized. `None` if `base` isn't
+    /// a triangle of the base icosahedron.
+    pub fn face(&self, coord: TileCoord) -> Option<usize> {
+        if coord.base >= self.base_cells.len() {
+            return None;
+        }
+        self.faces.get(&self.normalize(coord)).copied()
+    }
+
+    /// Brings a coordinate back inside a base triangle and onto the triangle with the lowest
+    /// index that has it. Like `offset`, `neighbors` and `is_pentagon`, panics if `base` isn't
+    /// a triangle of the base icosahedron.
+    pub fn normalize(&self, coord: TileCoord) -> TileCoord {
+        assert!(
+            coord.base < self.base_cells.len(),
+            "Base triangle {} of {:?} isn't on the base icosahedron",
+            coord.base,
+            coord
+        );
+        let mut base = coord.base;
+        let mut weights = [self.size - coord.q - coord.r, coord.q, coord.r];
+        // walk across the edge the point is furthest outside of until it's inside
+        loop {
+            let (corner, weight) = weights
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, weight)| **weight)
+                .map(|(corner, weight)| (corner, *weight))
+                .unwrap();
+            if weight >= 0 {
+                break;
+            }
+            let (neighbor, neighbor_weights) = self.unfold(base, weights, corner);
+            base = neighbor;
+            weights = neighbor_weights;
+        }
+
+        // points on edges and corners are also in the triangles across the edges they're on
+        let mut best = (base, weights);
+        let mut visited = vec![base];
+        let mut stack = vec![(base, weights)];
+        while let Some((base, weights)) = stack.pop() {
+            for corner in 0..3 {
+                if weights[corner] != 0 {
+                    continue;
+                }
+                let (neighbor, neighbor_weights) = self.unfold(base, weights, corner);
+                if visited.contains(&neighbor) {
+                    continue;
+                }
+                visited.push(neighbor);
+                stack.push((neighbor, neighbor_weights));
+                if neighbor < best.0 {
+                    best = (neighbor, neighbor_weights);
+                }
+            }
+        }
+        TileCoord {
+            base: best.0,
+            q: best.1[1],
+            r: best.1[2],
+        }
+    }
+
+    /// Moves a coordinate by an axial offset, normalized.
+    pub fn offset(&self, coord: TileCoord, q: i32, r: i32) -> TileCoord {
+        self.normalize(TileCoord {
+            base: coord.base,
+            q: coord.q + q,
+            r: coord.r + r,
+        })
+    }
+
+    /// The 6 tiles next to a coordinate, or 5 for a pentagon.
+    pub fn neighbors(&self, coord: TileCoord) -> Vec<TileCoord> {
+        let mut neighbors = vec![];
+        for (q, r) in AXIAL_DIRECTIONS.iter() {
+            let neighbor = self.offset(coord, *q, *r);
+            if !neighbors.contains(&neighbor) {
+                neighbors.push(neighbor);
+            }
+        }
+        neighbors
+    }
+
+    pub fn is_pentagon(&self, coord: TileCoord) -> bool {
+        let coord = self.normalize(coord);
+        let weights = [self.size - coord.q - coord.r, coord.q, coord.r];
+        weights.iter().filter(|weight| **weight == 0).count() == 2
+    }
+
+    /// Carries corner weights into the base triangle across the edge opposite a corner. The
+    /// corner unfolds onto the far corner of the neighbor minus the two corners of the edge.
+    fn unfold(&self, base: usize, weights: [i32; 3], corner: usize) -> (usize, [i32; 3]) {
+        let cell = self.base_cells[base];
+        let edge = [cell[(corner + 1) % 3], cell[(corner + 2) % 3]];
+        let edge_weights = [weights[(corner + 1) % 3], weights[(corner + 2) % 3]];
+        let neighbor = (0..self.base_cells.len())
+            .find(|neighbor| {
+                *neighbor != base
+                    && self.base_cells[*neighbor].contains(&edge[0])
+                    && self.base_cells[*neighbor].contains(&edge[1])
+            })
+            .expect("Base triangle edge has no neighbor");
+        let mut neighbor_weights = [0; 3];
+        for (neighbor_weight, vertex) in neighbor_weights
+            .iter_mut()
+            .zip(self.base_cells[neighbor].iter())
+        {
+            *neighbor_weight = if *vertex == edge[0] {
+                edge_weights[0] + weights[corner]
+            } else if *vertex == edge[1] {
+                edge_weights[1] + weights[corner]
+            } else {
+                -weights[corner]
+            };
+        }
+        (neighbor, neighbor_weights)
+    }
+}
+
+impl Polyhedron {
+    pub fn hex_grid(&self) -> HexGrid {
+        HexGrid::new(self)
+    }
+}
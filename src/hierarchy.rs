@@ -0,0 +1,98 @@
+use std::collections::HashMap;
+
+use crate::lattice::{lattice_cell, lattice_cell_index};
+use crate::{vertex_key, Polyhedron};
+
+/// Relates the faces of a polyhedron at one detail level to the faces of the same kind of
+/// polyhedron at the next detail level.
+#[derive(Debug, Clone)]
+pub struct LevelMapping {
+    /// For every face at the finer level, the face it is part of at the coarser level.
+    pub parents: Vec<usize>,
+    /// For every face at the coarser level, the faces at the finer level that are part of it.
+    pub children: Vec<Vec<usize>>,
+}
+
+impl LevelMapping {
+    /// Maps the faces of `new_isocahedron(_, detail + 1)` to the faces of
+    /// `new_isocahedron(_, detail)`. Every coarse triangle has exactly 4 children.
+    pub fn isocahedron(detail: u32) -> LevelMapping {
+        let cols = 2usize.pow(detail);
+        let child_cols = cols * 2;
+        let child_count = 20 * child_cols * child_cols;
+        let parents = (0..child_count)
+            .map(|cell_index| {
+                let (base, i, k, odd) = lattice_cell(cell_index, child_cols);
+                // centroid of the child in thirds of the child lattice
+                let offset = if odd { 2 } else { 1 };
+                let centroid_i = 3 * i + offset;
+                let centroid_k = 3 * k + offset;
+                let parent_i = centroid_i / 6;
+                let parent_k = centroid_k / 6;
+                let parent_odd = (centroid_i - 6 * parent_i) + (centroid_k - 6 * parent_k) > 6;
+                lattice_cell_index(base, parent_i, parent_k, parent_odd, cols)
+            })
+            .collect();
+        LevelMapping::from_parents(parents, 20 * cols * cols)
+    }
+
+    /// Maps the faces of `new_truncated_isocahedron(_, detail + 1)` to the faces of
+    /// `new_truncated_isocahedron(_, detail)`, for any triangulation.
+    ///
+    /// Faces that keep their center between levels map to themselves. The new faces centered
+    /// on the edges between two coarse faces belong to the one with the lower index.
+    pub fn truncated_isocahedron(detail: u32) -> LevelMapping {
+        let coarse = Polyhedron::new_isocahedron(1.0, detail);
+        let fine = Polyhedron::new_isocahedron(1.0, detail + 1);
+        let coarse_vertices: HashMap<_, usize> = coarse
+            .positions
+            .iter()
+            .enumerate()
+            .map(|(i, position)| (vertex_key(position.0), i))
+            .collect();
+
+        let mut parents: Vec<Option<usize>> = fine
+            .positions
+            .iter()
+            .map(|position| coarse_vertices.get(&vertex_key(position.0)).cloned())
+            .collect();
+        let mut edge_parents: Vec<Option<usize>> = vec![None; fine.positions.len()];
+        for cell in fine.cells.iter() {
+            let vertices = [cell.a, cell.b, cell.c];
+            for vertex in vertices.iter() {
+                if parents[*vertex].is_some() {
+                    continue;
+                }
+                for neighbor in vertices.iter() {
+                    if let Some(neighbor_parent) = parents[*neighbor] {
+                        edge_parents[*vertex] = Some(
+                            edge_parents[*vertex]
+                                .map_or(neighbor_parent, |parent| parent.min(neighbor_parent)),
+                        );
+                    }
+                }
+            }
+        }
+        for (parent, edge_parent) in parents.iter_mut().zip(edge_parents) {
+            if parent.is_none() {
+                *parent = edge_parent;
+            }
+        }
+
+        LevelMapping::from_parents(
+            parents
+                .iter()
+                .map(|parent| parent.expect("Vertex without a parent vertex"))
+                .collect(),
+            coarse.positions.len(),
+        )
+    }
+
+    fn from_parents(parents: Vec<usize>, parent_count: usize) -> LevelMapping {
+        let mut children = vec![vec![]; parent_count];
+        for (child, parent) in parents.iter().enumerate() {
+            children[*parent].push(child);
+        }
+        LevelMapping { parents, children }
+    }
+}
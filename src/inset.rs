@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::{ArraySerializedVector, Polyhedron};
+
+/// A sloped rim around the flat top of an inset face.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Bevel {
+    /// Width of the rim, as a fraction of the distance from the center of the face to its
+    /// edge.
+    pub width: f32,
+    /// How far the flat top is raised above the outer edge of the rim.
+    pub height: f32,
+}
+
+impl Polyhedron {
+    /// Shrinks every face toward its center by `amount`, a fraction of the distance from the
+    /// center to its edge, leaving gaps between the faces.
+    ///
+    /// Face `i` of the result is the shrunken face `i`, with its own vertices and flat
+    /// normals, so faces can be highlighted one at a time.
+    pub fn inset(&self, amount: f32, bevel: Option<Bevel>) -> Polyhedron {
+        let mut inset = Polyhedron::new();
+        for (face_index, face) in self.faces.iter().enumerate() {
+            let center = self.face_center(face_index);
+            let normal = self.face_normal(face_index);
+            let (top_scale, top_offset) = match bevel {
+                Some(bevel) => (1.0 - amount - bevel.width, normal * bevel.height),
+                None => (1.0 - amount, Vector3::new(0.0, 0.0, 0.0)),
+            };
+            let shrunk = |position: Vector3<f32>, scale: f32| center + (position - center) * scale;
+            let mut new_face = vec![];
+
+            let mut top_vertices: HashMap<usize, usize> = HashMap::new();
+            for cell_index in face.iter() {
+                let cell = &self.cells[*cell_index];
+                let mut new_indices = [0; 3];
+                for (new_index, old_index) in
+                    new_indices.iter_mut().zip([cell.a, cell.b, cell.c].iter())
+                {
+                    *new_index = *top_vertices.entry(*old_index).or_insert_with(|| {
+                        inset.push_vertex(
+                            shrunk(self.positions[*old_index].0, top_scale) + top_offset,
+                            normal,
+                            self.vertex_color(*old_index),
+                        )
+                    });
+                }
+                inset.push_facing(new_indices, normal);
+                new_face.push(inset.cells.len() - 1);
+            }
+
+            if bevel.is_some() {
+                let boundary = self.face_boundary(face_index);
+                for (i, start) in boundary.iter().enumerate() {
+                    let end = boundary[(i + 1) % boundary.len()];
+                    let outer_start = shrunk(self.positions[*start].0, 1.0 - amount);
+                    let outer_end = shrunk(self.positions[end].0, 1.0 - amount);
+                    let inner_start = shrunk(self.positions[*start].0, top_scale) + top_offset;
+                    let inner_end = shrunk(self.positions[end].0, top_scale) + top_offset;
+                    let mut rim_normal = (outer_end - outer_start).cross(inner_end - outer_start);
+                    if rim_normal.dot(normal) < 0.0 {
+                        rim_normal = -rim_normal;
+                    }
+                    let rim_normal = rim_normal.normalize();
+                    let rim: Vec<usize> = [
+                        (outer_start, *start),
+                        (outer_end, end),
+                        (inner_end, end),
+                        (inner_start, *start),
+                    ]
+                    .iter()
+                    .map(|(position, old_index)| {
+                        inset.push_vertex(*position, rim_normal, self.vertex_color(*old_index))
+                    })
+                    .collect();
+                    inset.push_facing([rim[0], rim[1], rim[2]], rim_normal);
+                    new_face.push(inset.cells.len() - 1);
+                    inset.push_facing([rim[0], rim[2], rim[3]], rim_normal);
+                    new_face.push(inset.cells.len() - 1);
+                }
+            }
+
+            inset.faces.push(new_face);
+        }
+        inset.face_colors = self
+            .face_colors
+            .iter()
+            .map(|color| ArraySerializedVector(color.0))
+            .collect();
+        inset.face_elevations = self.face_elevations.clone();
+        inset
+    }
+}
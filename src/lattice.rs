@@ -0,0 +1,106 @@
+use cgmath::prelude::*;
+use cgmath::{Matrix3, Vector3};
+
+use crate::Polyhedron;
+
+/// Furthest a face center can be from the lattice point or lattice triangle center it rounds
+/// to, in steps of the lattice, for the face to be part of a regular icosahedron or hexsphere.
+/// The centers of regular faces stay within about 0.09 steps.
+pub(crate) const LATTICE_TOLERANCE: f32 = 0.2;
+
+/// Finds where directions fall on the triangles of the base icosahedron, whose flat
+/// triangles are the lattices every icosahedron and hexsphere vertex is projected from.
+pub(crate) struct BaseLattice {
+    corners: Vec<[Vector3<f32>; 3]>,
+    inverses: Vec<Matrix3<f32>>,
+}
+
+impl BaseLattice {
+    pub fn new() -> BaseLattice {
+        let base = Polyhedron::base_isocahedron();
+        let corners: Vec<[Vector3<f32>; 3]> = base
+            .cells
+            .iter()
+            .map(|cell| {
+                [
+                    base.positions[cell.a].0,
+                    base.positions[cell.b].0,
+                    base.positions[cell.c].0,
+                ]
+            })
+            .collect();
+        let inverses = corners
+            .iter()
+            .map(|[a, b, c]| {
+                Matrix3::from_cols(*a, *b, *c)
+                    .invert()
+                    .expect("Degenerate base triangle")
+            })
+            .collect();
+        BaseLattice { corners, inverses }
+    }
+
+    /// Weights of the corners of a base triangle that make the point where a direction
+    /// crosses the triangle's plane. They are all positive when the direction passes through
+    /// the triangle, and add up to 1.
+    pub fn weights(&self, base: usize, direction: Vector3<f32>) -> Vector3<f32> {
+        let weights = self.inverses[base] * direction;
+        weights / (weights.x + weights.y + weights.z)
+    }
+
+    /// The base triangle a direction passes through and the weights of its corners. Directions
+    /// through an edge or corner belong to the base triangle with the lowest index.
+    pub fn locate(&self, direction: Vector3<f32>) -> (usize, Vector3<f32>) {
+        let mut best: Option<(usize, Vector3<f32>, f32)> = None;
+        for base in 0..self.corners.len() {
+            // skip the triangles on the opposite side of the sphere
+            let [a, b, c] = self.corners[base];
+            if (a + b + c).dot(direction) <= 0.0 {
+                continue;
+            }
+            let weights = self.weights(base, direction);
+            let min_weight = weights.x.min(weights.y).min(weights.z);
+            if min_weight >= -1e-6 {
+                return (base, weights);
+            }
+            match best {
+                Some((_, _, best_min)) if best_min >= min_weight => {}
+                _ => best = Some((base, weights, min_weight)),
+            }
+        }
+        let (base, weights, _) = best.expect("Direction doesn't point at any base triangle");
+        (base, weights)
+    }
+
+    /// The triangle of a lattice with `cols` divisions per edge that contains the point with
+    /// the given corner weights, as `(i, k, odd)` like `lattice_cell`.
+    pub fn cell(&self, weights: Vector3<f32>, cols: usize) -> (usize, usize, bool) {
+        let fi = (weights.z * cols as f32).max(0.0);
+        let fk = (weights.y * cols as f32).max(0.0);
+        let i = (fi.floor() as usize).min(cols - 1);
+        let k = fk.floor() as usize;
+        if k > cols - 1 - i {
+            return (i, cols - 1 - i, false);
+        }
+        let odd = (fi - i as f32) + (fk - k as f32) > 1.0 && k < cols - 1 - i;
+        (i, k, odd)
+    }
+}
+
+/// Splits the index of a triangle made by `subdivide_triangle` into its base icosahedron
+/// face, its row and column in the lattice, and whether it points away from the row.
+pub(crate) fn lattice_cell(cell_index: usize, cols: usize) -> (usize, usize, usize, bool) {
+    let base = cell_index / (cols * cols);
+    let mut local = cell_index % (cols * cols);
+    let mut i = 0;
+    while local >= 2 * (cols - i) - 1 {
+        local -= 2 * (cols - i) - 1;
+        i += 1;
+    }
+    (base, i, local / 2, local % 2 == 1)
+}
+
+/// Inverse of `lattice_cell`.
+pub(crate) fn lattice_cell_index(base: usize, i: usize, k: usize, odd: bool, cols: usize) -> usize {
+    base * cols * cols + 2 * cols * i - i * i + 2 * k + odd as usize
+}
@@ -1,17 +1,95 @@
 extern crate cgmath;
 extern crate rand;
 
+mod biome;
+mod bvh;
+mod chunk;
+mod colormap;
+mod delaunay;
+mod displace;
+mod extrude;
+mod graph;
+mod grid;
+mod hierarchy;
+mod inset;
+mod lattice;
+mod lines;
+mod locator;
+mod patch;
+mod plates;
+mod points;
+mod rivers;
+mod tangent;
+mod terrain;
+mod tile_id;
+mod uv;
+
 use std::collections::HashMap;
 use std::ops::AddAssign;
 
 use cgmath::prelude::*;
-use cgmath::Vector3;
+use cgmath::{Vector2, Vector3, Vector4};
 use rand::prelude::*;
 use serde::ser::{SerializeSeq, Serializer};
 use serde::Serialize;
 
+pub use biome::{Biome, BiomeThresholds, Climate, ClimateSettings};
+pub use bvh::{Bvh, RayHit};
+pub use chunk::{Bounds, Chunk};
+pub use colormap::Colormap;
+pub use graph::{TileGraph, TilePath};
+pub use grid::{HexGrid, TileCoord};
+pub use hierarchy::LevelMapping;
+pub use inset::Bevel;
+pub use lines::LineMesh;
+pub use locator::FaceLocator;
+pub use patch::Patch;
+pub use plates::{BoundaryKind, Plate, PlateBoundary, Plates};
+pub use points::PointDistribution;
+pub use rivers::{Drainage, River};
+pub use terrain::{Fractal, NoiseBasis, TerrainNoise};
+pub use tile_id::{TileId, MAX_TILE_ID_LEVEL};
+pub use uv::UvMode;
+
 const VERT_CACHE_PRECISION: f32 = 10000_f32;
 
+type VertexKey = (i32, i32, i32);
+
+/// How the hexagon and pentagon faces of a truncated polyhedron are split into triangles.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Triangulation {
+    /// Two triangles per corner, between the face center, the corner and the midpoints of the
+    /// corner's edges (12 per hexagon, 10 per pentagon).
+    Spokes,
+    /// One triangle per edge, fanned out from the face center (6 per hexagon, 5 per pentagon).
+    CenterFan,
+    /// A fan from the first corner of the face with no extra vertices (4 per hexagon, 3 per
+    /// pentagon).
+    MinimalFan,
+}
+
+/// How vertex normals are computed.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+    /// Normals point straight out from the center of the sphere.
+    Analytic,
+    /// Area weighted average of the normals of the triangles around each vertex.
+    Smooth,
+    /// Each face's vertices get the average normal of the whole face.
+    Flat,
+}
+
+/// Per-face values that faces can be colored by.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FaceAttribute {
+    /// Latitude of the face center in radians, with the poles on the y axis.
+    Latitude,
+    /// Number of sides of the face, which separates pentagons from hexagons.
+    TileType,
+    /// Surface area of the face.
+    Area,
+}
+
 #[derive(Debug)]
 pub struct Triangle {
     pub a: usize,
@@ -42,14 +120,32 @@ impl Serialize for Triangle {
 #[derive(Debug)]
 pub struct ArraySerializedVector(pub Vector3<f32>);
 
+#[derive(Debug)]
+pub struct ArraySerializedVector2(pub Vector2<f32>);
+
+#[derive(Debug)]
+pub struct ArraySerializedVector4(pub Vector4<f32>);
+
 #[derive(Serialize, Debug)]
 pub struct Polyhedron {
     pub positions: Vec<ArraySerializedVector>,
     pub cells: Vec<Triangle>,
     pub normals: Vec<ArraySerializedVector>,
     pub colors: Vec<ArraySerializedVector>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tangents: Vec<ArraySerializedVector4>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub uvs: Vec<ArraySerializedVector2>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub face_colors: Vec<ArraySerializedVector>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub cell_faces: Vec<usize>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub face_elevations: Vec<f32>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub vertex_elevations: Vec<f32>,
     #[serde(skip)]
-    added_vert_cache: HashMap<(i32, i32, i32), usize>,
+    added_vert_cache: HashMap<VertexKey, usize>,
     faces: Vec<Vec<usize>>,
 }
 
@@ -67,12 +163,46 @@ impl Serialize for ArraySerializedVector {
     }
 }
 
+impl Serialize for ArraySerializedVector2 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let values = vec![self.0.x, self.0.y];
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
+impl Serialize for ArraySerializedVector4 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let values = vec![self.0.x, self.0.y, self.0.z, self.0.w];
+        let mut seq = serializer.serialize_seq(Some(values.len()))?;
+        for value in values {
+            seq.serialize_element(&value)?;
+        }
+        seq.end()
+    }
+}
+
 impl AddAssign for ArraySerializedVector {
     fn add_assign(&mut self, other: Self) {
         *self = Self(self.0 + other.0);
     }
 }
 
+impl Default for Polyhedron {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Polyhedron {
     pub fn new() -> Polyhedron {
         Polyhedron {
@@ -80,13 +210,29 @@ impl Polyhedron {
             cells: vec![],
             normals: vec![],
             colors: vec![],
+            tangents: vec![],
+            uvs: vec![],
+            face_colors: vec![],
+            cell_faces: vec![],
+            face_elevations: vec![],
+            vertex_elevations: vec![],
             added_vert_cache: HashMap::new(),
             faces: vec![],
         }
     }
 
     pub fn new_isocahedron(radius: f32, detail: u32) -> Polyhedron {
-        let t = (1.0 + (5.0 as f32).sqrt()) / 2.0;
+        let base_isocahedron = Polyhedron::base_isocahedron();
+        let mut subdivided = Polyhedron::new();
+        subdivided.subdivide(base_isocahedron, radius, detail);
+        subdivided.triangles_to_faces();
+        subdivided
+    }
+
+    /// The 20 triangles every icosahedron and hexsphere is subdivided from. Its vertices are
+    /// not on the unit sphere.
+    fn base_isocahedron() -> Polyhedron {
+        let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
         let mut base_isocahedron = Polyhedron {
             positions: vec![],
             cells: vec![
@@ -111,10 +257,7 @@ impl Polyhedron {
                 Triangle::new(8, 6, 7),
                 Triangle::new(9, 8, 1),
             ],
-            normals: vec![],
-            colors: vec![],
-            added_vert_cache: HashMap::new(),
-            faces: vec![],
+            ..Polyhedron::new()
         };
         base_isocahedron.add_position(Vector3::new(-1.0, t, 0.0));
         base_isocahedron.add_position(Vector3::new(1.0, t, 0.0));
@@ -128,17 +271,25 @@ impl Polyhedron {
         base_isocahedron.add_position(Vector3::new(t, 0.0, 1.0));
         base_isocahedron.add_position(Vector3::new(-t, 0.0, -1.0));
         base_isocahedron.add_position(Vector3::new(-t, 0.0, 1.0));
-
-        let mut subdivided = Polyhedron::new();
-        subdivided.subdivide(base_isocahedron, radius, detail);
-        subdivided.triangles_to_faces();
-        subdivided
+        base_isocahedron
     }
 
     pub fn new_truncated_isocahedron(radius: f32, detail: u32) -> Polyhedron {
+        Polyhedron::new_truncated_isocahedron_with_triangulation(
+            radius,
+            detail,
+            Triangulation::Spokes,
+        )
+    }
+
+    pub fn new_truncated_isocahedron_with_triangulation(
+        radius: f32,
+        detail: u32,
+        triangulation: Triangulation,
+    ) -> Polyhedron {
         let isocahedron = Polyhedron::new_isocahedron(radius, detail);
         let mut truncated_isocahedron = Polyhedron::new();
-        truncated_isocahedron.truncated(isocahedron);
+        truncated_isocahedron.truncated(isocahedron, triangulation);
         truncated_isocahedron
     }
 
@@ -170,16 +321,15 @@ impl Polyhedron {
 
         for i in 0..=cols {
             new_vertices.push(vec![]);
-            let aj = a.clone().lerp(c, i as f32 / cols as f32);
-            let bj = b.clone().lerp(c, i as f32 / cols as f32);
+            let aj = a.lerp(c, i as f32 / cols as f32);
+            let bj = b.lerp(c, i as f32 / cols as f32);
             let rows = cols - i;
 
             for j in 0..=rows {
                 if j == 0 && i == cols {
                     new_vertices[i].push(aj.normalize() * radius);
                 } else {
-                    new_vertices[i]
-                        .push(aj.clone().lerp(bj, j as f32 / rows as f32).normalize() * radius);
+                    new_vertices[i].push(aj.lerp(bj, j as f32 / rows as f32).normalize() * radius);
                 }
             }
         }
@@ -205,13 +355,9 @@ impl Polyhedron {
     }
 
     fn add_position(&mut self, vertex: Vector3<f32>) -> usize {
-        let vertex_key = (
-            (vertex.x * VERT_CACHE_PRECISION).round() as i32,
-            (vertex.y * VERT_CACHE_PRECISION).round() as i32,
-            (vertex.z * VERT_CACHE_PRECISION).round() as i32,
-        );
+        let vertex_key = vertex_key(vertex);
         if let Some(added_vert_index) = self.added_vert_cache.get(&vertex_key) {
-            return *added_vert_index;
+            *added_vert_index
         } else {
             self.positions.push(ArraySerializedVector(vertex));
             self.normals
@@ -220,11 +366,11 @@ impl Polyhedron {
                 .push(ArraySerializedVector(Vector3::new(1.0, 1.0, 1.0)));
             let added_index = self.positions.len() - 1;
             self.added_vert_cache.insert(vertex_key, added_index);
-            return added_index;
+            added_index
         }
     }
 
-    fn truncated(&mut self, other: Polyhedron) {
+    fn truncated(&mut self, other: Polyhedron, triangulation: Triangulation) {
         let vert_to_faces = other.vert_to_faces();
         let original_vert_count = other.positions.len();
         let triangle_centroids = other.triangle_centroids();
@@ -241,6 +387,16 @@ impl Polyhedron {
 
             let center_point = find_center_of_triangles(faces, &triangle_centroids);
 
+            if triangulation != Triangulation::Spokes {
+                let corners: Vec<Vector3<f32>> = faces
+                    .iter()
+                    .map(|face_index| triangle_centroids[face_index])
+                    .collect();
+                let corners = sort_around(other.positions[i].0, center_point, corners);
+                self.add_face_polygon(center_point, &corners, triangulation);
+                continue;
+            }
+
             let mut new_face = Vec::new();
 
             for face_index in faces.iter().rev() {
@@ -295,6 +451,59 @@ impl Polyhedron {
         println!("pentagons: {}", pent_count);
     }
 
+    /// Adds a face from its center and its corners, which must be in counter-clockwise order
+    /// when viewed from outside the polyhedron.
+    fn add_face_polygon(
+        &mut self,
+        center: Vector3<f32>,
+        corners: &[Vector3<f32>],
+        triangulation: Triangulation,
+    ) {
+        let mut new_face = Vec::new();
+        let corner_indices: Vec<usize> = corners
+            .iter()
+            .map(|corner| self.add_position(*corner))
+            .collect();
+        let corner_count = corner_indices.len();
+        match triangulation {
+            Triangulation::Spokes => {
+                let center_index = self.add_position(center);
+                for k in 0..corner_count {
+                    let next = (k + 1) % corner_count;
+                    let mid_index = self.add_position(corners[k].lerp(corners[next], 0.5));
+                    self.cells
+                        .push(Triangle::new(center_index, corner_indices[k], mid_index));
+                    new_face.push(self.cells.len() - 1);
+                    self.cells
+                        .push(Triangle::new(center_index, mid_index, corner_indices[next]));
+                    new_face.push(self.cells.len() - 1);
+                }
+            }
+            Triangulation::CenterFan => {
+                let center_index = self.add_position(center);
+                for k in 0..corner_count {
+                    self.cells.push(Triangle::new(
+                        center_index,
+                        corner_indices[k],
+                        corner_indices[(k + 1) % corner_count],
+                    ));
+                    new_face.push(self.cells.len() - 1);
+                }
+            }
+            Triangulation::MinimalFan => {
+                for k in 1..corner_count - 1 {
+                    self.cells.push(Triangle::new(
+                        corner_indices[0],
+                        corner_indices[k],
+                        corner_indices[k + 1],
+                    ));
+                    new_face.push(self.cells.len() - 1);
+                }
+            }
+        }
+        self.faces.push(new_face);
+    }
+
     pub fn unique_vertices(&mut self, other: Polyhedron) {
         for triangle in other.cells {
             let vertex_a = other.positions[triangle.a].0;
@@ -316,11 +525,176 @@ impl Polyhedron {
                 .push(ArraySerializedVector(Vector3::new(1.0, 1.0, 1.0)));
             self.colors
                 .push(ArraySerializedVector(Vector3::new(1.0, 1.0, 1.0)));
+            if !other.uvs.is_empty() {
+                self.uvs
+                    .push(ArraySerializedVector2(other.uvs[triangle.a].0));
+                self.uvs
+                    .push(ArraySerializedVector2(other.uvs[triangle.b].0));
+                self.uvs
+                    .push(ArraySerializedVector2(other.uvs[triangle.c].0));
+            }
+            if !other.vertex_elevations.is_empty() {
+                self.vertex_elevations
+                    .push(other.vertex_elevations[triangle.a]);
+                self.vertex_elevations
+                    .push(other.vertex_elevations[triangle.b]);
+                self.vertex_elevations
+                    .push(other.vertex_elevations[triangle.c]);
+            }
             let added_index = self.positions.len() - 1;
             self.cells
                 .push(Triangle::new(added_index - 2, added_index - 1, added_index));
         }
         self.faces = other.faces;
+        self.face_elevations = other.face_elevations;
+    }
+
+    /// Like `unique_vertices`, but only duplicates vertices shared between faces, so every
+    /// face gets its own set of vertices that its triangles share.
+    pub fn unique_face_vertices(&mut self, other: Polyhedron) {
+        let cell_faces = other.cell_face_indices();
+        let mut face_vertex_maps: Vec<HashMap<usize, usize>> =
+            vec![HashMap::new(); other.faces.len()];
+        for (cell_index, triangle) in other.cells.iter().enumerate() {
+            let face_vertex_map = &mut face_vertex_maps[cell_faces[cell_index]];
+            let mut new_indices = [0; 3];
+            for (new_index, old_index) in new_indices
+                .iter_mut()
+                .zip([triangle.a, triangle.b, triangle.c].iter())
+            {
+                *new_index = *face_vertex_map.entry(*old_index).or_insert_with(|| {
+                    self.positions
+                        .push(ArraySerializedVector(other.positions[*old_index].0));
+                    self.normals
+                        .push(ArraySerializedVector(other.normals[*old_index].0));
+                    self.colors
+                        .push(ArraySerializedVector(other.colors[*old_index].0));
+                    if !other.uvs.is_empty() {
+                        self.uvs
+                            .push(ArraySerializedVector2(other.uvs[*old_index].0));
+                    }
+                    if !other.vertex_elevations.is_empty() {
+                        self.vertex_elevations
+                            .push(other.vertex_elevations[*old_index]);
+                    }
+                    self.positions.len() - 1
+                });
+            }
+            self.cells.push(Triangle::new(
+                new_indices[0],
+                new_indices[1],
+                new_indices[2],
+            ));
+        }
+        self.faces = other.faces;
+        self.face_colors = other.face_colors;
+        self.face_elevations = other.face_elevations;
+    }
+
+    /// Adds a copy of a vertex that isn't shared with any triangle yet.
+    fn duplicate_vertex(&mut self, vertex_index: usize) -> usize {
+        self.positions
+            .push(ArraySerializedVector(self.positions[vertex_index].0));
+        self.normals
+            .push(ArraySerializedVector(self.normals[vertex_index].0));
+        self.colors
+            .push(ArraySerializedVector(self.colors[vertex_index].0));
+        if !self.uvs.is_empty() {
+            self.uvs
+                .push(ArraySerializedVector2(self.uvs[vertex_index].0));
+        }
+        if !self.tangents.is_empty() {
+            self.tangents
+                .push(ArraySerializedVector4(self.tangents[vertex_index].0));
+        }
+        if !self.vertex_elevations.is_empty() {
+            self.vertex_elevations
+                .push(self.vertex_elevations[vertex_index]);
+        }
+        self.positions.len() - 1
+    }
+
+    /// Copies the given faces, with only the vertices they use, into a new polyhedron. Face
+    /// `i` of the new polyhedron is face `face_indices[i]` of this one.
+    pub fn extract_faces(&self, face_indices: &[usize]) -> Polyhedron {
+        let mut extracted = Polyhedron::new();
+        let mut vertex_map: HashMap<usize, usize> = HashMap::new();
+        for face_index in face_indices {
+            let mut new_face = vec![];
+            for cell_index in self.faces[*face_index].iter() {
+                let cell = &self.cells[*cell_index];
+                let mut new_indices = [0; 3];
+                for (new_index, old_index) in
+                    new_indices.iter_mut().zip([cell.a, cell.b, cell.c].iter())
+                {
+                    *new_index = *vertex_map.entry(*old_index).or_insert_with(|| {
+                        extracted
+                            .positions
+                            .push(ArraySerializedVector(self.positions[*old_index].0));
+                        extracted
+                            .normals
+                            .push(ArraySerializedVector(self.normals[*old_index].0));
+                        extracted
+                            .colors
+                            .push(ArraySerializedVector(self.colors[*old_index].0));
+                        if !self.uvs.is_empty() {
+                            extracted
+                                .uvs
+                                .push(ArraySerializedVector2(self.uvs[*old_index].0));
+                        }
+                        if !self.tangents.is_empty() {
+                            extracted
+                                .tangents
+                                .push(ArraySerializedVector4(self.tangents[*old_index].0));
+                        }
+                        if !self.vertex_elevations.is_empty() {
+                            extracted
+                                .vertex_elevations
+                                .push(self.vertex_elevations[*old_index]);
+                        }
+                        extracted.positions.len() - 1
+                    });
+                }
+                extracted.cells.push(Triangle::new(
+                    new_indices[0],
+                    new_indices[1],
+                    new_indices[2],
+                ));
+                new_face.push(extracted.cells.len() - 1);
+            }
+            extracted.faces.push(new_face);
+            if !self.face_colors.is_empty() {
+                extracted
+                    .face_colors
+                    .push(ArraySerializedVector(self.face_colors[*face_index].0));
+            }
+            if !self.face_elevations.is_empty() {
+                extracted
+                    .face_elevations
+                    .push(self.face_elevations[*face_index]);
+            }
+        }
+        if !self.cell_faces.is_empty() {
+            extracted.assign_cell_faces();
+        }
+        extracted
+    }
+
+    /// Index of the face each triangle belongs to.
+    pub fn cell_face_indices(&self) -> Vec<usize> {
+        let mut cell_faces = vec![0; self.cells.len()];
+        for (face_index, face) in self.faces.iter().enumerate() {
+            for cell_index in face {
+                cell_faces[*cell_index] = face_index;
+            }
+        }
+        cell_faces
+    }
+
+    /// Fills `cell_faces` so that face colors can be looked up per triangle from
+    /// `face_colors` instead of per vertex from `colors`.
+    pub fn assign_cell_faces(&mut self) {
+        self.cell_faces = self.cell_face_indices();
     }
 
     fn vert_to_faces(&self) -> HashMap<usize, Vec<usize>> {
@@ -360,6 +734,7 @@ impl Polyhedron {
         triangle_centroids
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn calculate_mid_centroid(
         &self,
         spoke_vertex_index: usize,
@@ -377,14 +752,14 @@ impl Polyhedron {
         if let Some(mid_centroid) =
             mid_centroid_cache.get(&(spoke_vertex_index, vertex_index, adj_face_index))
         {
-            return *mid_centroid;
+            *mid_centroid
         } else {
-            let mid_centroid = centroid.clone().lerp(adj_centroid, 0.5);
+            let mid_centroid = centroid.lerp(adj_centroid, 0.5);
             mid_centroid_cache.insert(
                 (spoke_vertex_index, vertex_index, adj_face_index),
                 mid_centroid,
             );
-            return mid_centroid;
+            mid_centroid
         }
     }
 
@@ -444,24 +819,12 @@ impl Polyhedron {
         }
     }
 
+    /// Gives every vertex of a face the face's normal. Vertices shared between faces get the
+    /// average of their faces' normals, so call this on a polyhedron with unique face vertices
+    /// for flat shading.
     pub fn compute_face_normals(&mut self) {
-        let origin = Vector3::new(0.0, 0.0, 0.0);
         for i in 0..self.faces.len() {
-            let first_cell = &self.cells[self.faces[i][0]];
-
-            let vertex_a = &self.positions[first_cell.a].0;
-            let vertex_b = &self.positions[first_cell.b].0;
-            let vertex_c = &self.positions[first_cell.c].0;
-
-            let e1 = vertex_a - vertex_b;
-            let e2 = vertex_c - vertex_b;
-            let mut normal = e1.cross(e2);
-
-            // detect and correct inverted normal
-            let dist = vertex_b - origin;
-            if normal.dot(dist) < 0.0 {
-                normal *= -1.0;
-            }
+            let normal = self.face_normal(i);
 
             for c in 0..self.faces[i].len() {
                 let face_cell = &self.cells[self.faces[i][c]];
@@ -481,21 +844,287 @@ impl Polyhedron {
         }
     }
 
+    /// Points every normal straight out from the center of the sphere.
+    pub fn compute_sphere_normals(&mut self) {
+        for (normal, position) in self.normals.iter_mut().zip(self.positions.iter()) {
+            *normal = ArraySerializedVector(position.0.normalize());
+        }
+    }
+
+    /// Replaces the normals using the given mode.
+    pub fn compute_normals(&mut self, mode: NormalMode) {
+        for normal in self.normals.iter_mut() {
+            *normal = ArraySerializedVector(Vector3::new(0.0, 0.0, 0.0));
+        }
+        match mode {
+            NormalMode::Analytic => self.compute_sphere_normals(),
+            NormalMode::Smooth => self.compute_triangle_normals(),
+            NormalMode::Flat => self.compute_face_normals(),
+        }
+    }
+
+    /// Area weighted average of the normals of all triangles in a face.
+    pub fn face_normal(&self, face_index: usize) -> Vector3<f32> {
+        let origin = Vector3::new(0.0, 0.0, 0.0);
+        let mut normal = Vector3::new(0.0, 0.0, 0.0);
+        for cell_index in self.faces[face_index].iter() {
+            let cell = &self.cells[*cell_index];
+            let vertex_a = &self.positions[cell.a].0;
+            let vertex_b = &self.positions[cell.b].0;
+            let vertex_c = &self.positions[cell.c].0;
+
+            let e1 = vertex_a - vertex_b;
+            let e2 = vertex_c - vertex_b;
+            let mut cell_normal = e1.cross(e2);
+
+            // detect and correct inverted normal
+            let dist = vertex_b - origin;
+            if cell_normal.dot(dist) < 0.0 {
+                cell_normal *= -1.0;
+            }
+            normal += cell_normal;
+        }
+        normal.normalize()
+    }
+
     pub fn assign_random_face_colors(&mut self) {
         let mut rng = rand::thread_rng();
-        for i in 0..self.faces.len() {
-            let face_color = Vector3::new(rng.gen(), rng.gen(), rng.gen());
+        let face_colors: Vec<Vector3<f32>> = (0..self.faces.len())
+            .map(|_| Vector3::new(rng.gen(), rng.gen(), rng.gen()))
+            .collect();
+        self.assign_face_colors(&face_colors);
+    }
 
-            for c in 0..self.faces[i].len() {
-                let face_cell = &self.cells[self.faces[i][c]];
+    /// Sets `face_colors` and every vertex of each face to that face's color.
+    ///
+    /// Vertices shared between faces take the color of the last face, so call this on a
+    /// polyhedron with unique vertices to get solid face colors.
+    pub fn assign_face_colors(&mut self, face_colors: &[Vector3<f32>]) {
+        self.face_colors = face_colors
+            .iter()
+            .map(|color| ArraySerializedVector(*color))
+            .collect();
+        for (face, face_color) in self.faces.iter().zip(face_colors) {
+            for cell_index in face {
+                let face_cell = &self.cells[*cell_index];
 
-                self.colors[face_cell.a] = ArraySerializedVector(face_color);
-                self.colors[face_cell.b] = ArraySerializedVector(face_color);
-                self.colors[face_cell.c] = ArraySerializedVector(face_color);
+                self.colors[face_cell.a] = ArraySerializedVector(*face_color);
+                self.colors[face_cell.b] = ArraySerializedVector(*face_color);
+                self.colors[face_cell.c] = ArraySerializedVector(*face_color);
             }
         }
     }
 
+    /// Colors faces by mapping per-face values onto a colormap, scaled so the smallest value
+    /// gets the start of the colormap and the largest value gets the end.
+    pub fn color_faces_by_values(&mut self, values: &[f32], colormap: &Colormap) {
+        let min = values.iter().cloned().fold(f32::INFINITY, f32::min);
+        let max = values.iter().cloned().fold(f32::NEG_INFINITY, f32::max);
+        let range = max - min;
+        let face_colors: Vec<Vector3<f32>> = values
+            .iter()
+            .map(|value| {
+                if range > 0.0 {
+                    colormap.sample((value - min) / range)
+                } else {
+                    colormap.sample(0.0)
+                }
+            })
+            .collect();
+        self.assign_face_colors(&face_colors);
+    }
+
+    /// Colors faces by a scalar function of the polyhedron and face index.
+    pub fn color_faces_with<F>(&mut self, scalar: F, colormap: &Colormap)
+    where
+        F: Fn(&Polyhedron, usize) -> f32,
+    {
+        let values: Vec<f32> = (0..self.faces.len()).map(|i| scalar(self, i)).collect();
+        self.color_faces_by_values(&values, colormap);
+    }
+
+    pub fn color_faces_by_attribute(&mut self, attribute: FaceAttribute, colormap: &Colormap) {
+        let values = self.face_attribute_values(attribute);
+        self.color_faces_by_values(&values, colormap);
+    }
+
+    pub fn face_attribute_values(&self, attribute: FaceAttribute) -> Vec<f32> {
+        (0..self.faces.len())
+            .map(|i| match attribute {
+                FaceAttribute::Latitude => {
+                    let center = self.face_center(i);
+                    (center.y / center.magnitude()).clamp(-1.0, 1.0).asin()
+                }
+                FaceAttribute::TileType => self.face_corners(i).len() as f32,
+                FaceAttribute::Area => self.face_area(i),
+            })
+            .collect()
+    }
+
+    pub fn face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    /// Center of a face, the average of its corners.
+    pub fn face_center(&self, face_index: usize) -> Vector3<f32> {
+        let corners = self.face_corners(face_index);
+        let mut center = Vector3::new(0.0, 0.0, 0.0);
+        for corner in corners.iter() {
+            center += self.positions[*corner].0;
+        }
+        center / corners.len() as f32
+    }
+
+    pub fn face_area(&self, face_index: usize) -> f32 {
+        let mut area = 0.0;
+        for cell_index in self.faces[face_index].iter() {
+            let cell = &self.cells[*cell_index];
+            let e1 = self.positions[cell.b].0 - self.positions[cell.a].0;
+            let e2 = self.positions[cell.c].0 - self.positions[cell.a].0;
+            area += e1.cross(e2).magnitude() / 2.0;
+        }
+        area
+    }
+
+    /// Faces that share an edge with each face, sorted by index. Faces are matched by the
+    /// positions of their vertices, so this works on polyhedra with unique vertices too.
+    pub fn face_adjacency(&self) -> Vec<Vec<usize>> {
+        let mut edge_faces: HashMap<(VertexKey, VertexKey), Vec<usize>> = HashMap::new();
+        for face_index in 0..self.faces.len() {
+            let boundary = self.face_boundary(face_index);
+            for (i, vertex_index) in boundary.iter().enumerate() {
+                let start = vertex_key(self.positions[*vertex_index].0);
+                let end = vertex_key(self.positions[boundary[(i + 1) % boundary.len()]].0);
+                let edge_key = if start < end {
+                    (start, end)
+                } else {
+                    (end, start)
+                };
+                edge_faces.entry(edge_key).or_default().push(face_index);
+            }
+        }
+
+        let mut adjacency: Vec<Vec<usize>> = vec![vec![]; self.faces.len()];
+        for faces in edge_faces.values() {
+            for face in faces {
+                for other in faces {
+                    if face != other {
+                        adjacency[*face].push(*other);
+                    }
+                }
+            }
+        }
+        for neighbors in adjacency.iter_mut() {
+            neighbors.sort_unstable();
+            neighbors.dedup();
+        }
+        adjacency
+    }
+
+    /// Vertices around the outline of a face, in counter-clockwise order when viewed from
+    /// outside the polyhedron. Vertices that only split an edge in two are included.
+    pub fn face_boundary(&self, face_index: usize) -> Vec<usize> {
+        let mut edge_counts: HashMap<(VertexKey, VertexKey), (usize, usize, u32)> = HashMap::new();
+        for cell_index in self.faces[face_index].iter() {
+            let cell = &self.cells[*cell_index];
+            for (from, to) in [(cell.a, cell.b), (cell.b, cell.c), (cell.c, cell.a)].iter() {
+                let from_key = vertex_key(self.positions[*from].0);
+                let to_key = vertex_key(self.positions[*to].0);
+                let edge_key = if from_key < to_key {
+                    (from_key, to_key)
+                } else {
+                    (to_key, from_key)
+                };
+                edge_counts.entry(edge_key).or_insert((*from, *to, 0)).2 += 1;
+            }
+        }
+
+        let mut links: HashMap<VertexKey, Vec<usize>> = HashMap::new();
+        let mut start: Option<usize> = None;
+        for (from, to, count) in edge_counts.values() {
+            if *count != 1 {
+                continue;
+            }
+            links
+                .entry(vertex_key(self.positions[*from].0))
+                .or_default()
+                .push(*to);
+            links
+                .entry(vertex_key(self.positions[*to].0))
+                .or_default()
+                .push(*from);
+            start = Some(start.map_or(*from, |start| start.min(*from)));
+        }
+
+        let mut boundary = vec![];
+        let start = match start {
+            Some(start) => start,
+            None => return boundary,
+        };
+        let start_key = vertex_key(self.positions[start].0);
+        let mut previous_key = start_key;
+        let mut current = start;
+        loop {
+            boundary.push(current);
+            let current_key = vertex_key(self.positions[current].0);
+            let next = links[&current_key]
+                .iter()
+                .find(|next| {
+                    let next_key = vertex_key(self.positions[**next].0);
+                    next_key != previous_key && next_key != current_key
+                })
+                .or_else(|| links[&current_key].first());
+            match next {
+                Some(next) if vertex_key(self.positions[*next].0) != start_key => {
+                    previous_key = current_key;
+                    current = *next;
+                }
+                _ => break,
+            }
+            if boundary.len() > links.len() {
+                break;
+            }
+        }
+
+        // orient the boundary counter-clockwise around the outward direction
+        let mut normal = Vector3::new(0.0, 0.0, 0.0);
+        let mut center = Vector3::new(0.0, 0.0, 0.0);
+        for (i, vertex_index) in boundary.iter().enumerate() {
+            let current = self.positions[*vertex_index].0;
+            let next = self.positions[boundary[(i + 1) % boundary.len()]].0;
+            normal += current.cross(next);
+            center += current;
+        }
+        if normal.dot(center) < 0.0 {
+            boundary.reverse();
+        }
+        boundary
+    }
+
+    /// Corners of a face, in the same order as `face_boundary` but skipping vertices that lie
+    /// in the middle of a straight edge.
+    pub fn face_corners(&self, face_index: usize) -> Vec<usize> {
+        let boundary = self.face_boundary(face_index);
+        if boundary.len() <= 3 {
+            return boundary;
+        }
+        boundary
+            .iter()
+            .enumerate()
+            .filter(|(i, vertex_index)| {
+                let previous =
+                    self.positions[boundary[(i + boundary.len() - 1) % boundary.len()]].0;
+                let current = self.positions[**vertex_index].0;
+                let next = self.positions[boundary[(i + 1) % boundary.len()]].0;
+                let incoming = current - previous;
+                let outgoing = next - current;
+                incoming.cross(outgoing).magnitude()
+                    > 1e-3 * incoming.magnitude() * outgoing.magnitude()
+            })
+            .map(|(_, vertex_index)| *vertex_index)
+            .collect()
+    }
+
     pub fn export_cells(&self) -> Vec<u32> {
         let mut cell_vec: Vec<u32> = vec![];
         for cell in &self.cells {
@@ -507,14 +1136,40 @@ impl Polyhedron {
     }
 }
 
+fn vertex_key(vertex: Vector3<f32>) -> VertexKey {
+    (
+        (vertex.x * VERT_CACHE_PRECISION).round() as i32,
+        (vertex.y * VERT_CACHE_PRECISION).round() as i32,
+        (vertex.z * VERT_CACHE_PRECISION).round() as i32,
+    )
+}
+
+/// Sorts points counter-clockwise around `center` when viewed from outside along `up`.
+fn sort_around(
+    up: Vector3<f32>,
+    center: Vector3<f32>,
+    mut points: Vec<Vector3<f32>>,
+) -> Vec<Vector3<f32>> {
+    let normal = up.normalize();
+    let reference = points[0] - center;
+    let u = (reference - normal * reference.dot(normal)).normalize();
+    let v = normal.cross(u);
+    let angle = |point: &Vector3<f32>| {
+        let offset = point - center;
+        offset.dot(v).atan2(offset.dot(u))
+    };
+    points.sort_by(|a, b| angle(a).partial_cmp(&angle(b)).unwrap());
+    points
+}
+
 fn calculate_centroid(pa: Vector3<f32>, pb: Vector3<f32>, pc: Vector3<f32>) -> Vector3<f32> {
-    let vab_half = (pb.clone() - pa) / 2.0;
-    let pab_half = pa.clone() + vab_half;
-    ((pc.clone() - pab_half) * (1.0 / 3.0)) + pab_half
+    let vab_half = (pb - pa) / 2.0;
+    let pab_half = pa + vab_half;
+    ((pc - pab_half) * (1.0 / 3.0)) + pab_half
 }
 
 fn find_center_of_triangles(
-    triangle_indices: &Vec<usize>,
+    triangle_indices: &[usize],
     triangle_centroids: &HashMap<usize, Vector3<f32>>,
 ) -> Vector3<f32> {
     let mut center_point: Vector3<f32> = Vector3::new(0.0, 0.0, 0.0);
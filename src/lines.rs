@@ -0,0 +1,92 @@
+use std::collections::{HashMap, HashSet};
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+use serde::Serialize;
+
+use crate::{vertex_key, ArraySerializedVector, Polyhedron, VertexKey};
+
+/// Line segments as pairs of indices into `positions`, for drawing outlines.
+#[derive(Serialize, Debug)]
+pub struct LineMesh {
+    pub positions: Vec<ArraySerializedVector>,
+    pub lines: Vec<[usize; 2]>,
+}
+
+impl Polyhedron {
+    /// The outlines of the faces, with every edge between two corners once and none of the
+    /// edges of the triangles inside faces.
+    pub fn face_edge_lines(&self) -> LineMesh {
+        let mut lines = LineMesh {
+            positions: vec![],
+            lines: vec![],
+        };
+        let mut vertices: HashMap<VertexKey, usize> = HashMap::new();
+        let mut edges: HashSet<(usize, usize)> = HashSet::new();
+        for face_index in 0..self.faces.len() {
+            let corners: Vec<usize> = self
+                .face_corners(face_index)
+                .iter()
+                .map(|corner| {
+                    let position = self.positions[*corner].0;
+                    *vertices.entry(vertex_key(position)).or_insert_with(|| {
+                        lines.positions.push(ArraySerializedVector(position));
+                        lines.positions.len() - 1
+                    })
+                })
+                .collect();
+            for (i, start) in corners.iter().enumerate() {
+                let end = corners[(i + 1) % corners.len()];
+                let edge = if *start < end {
+                    (*start, end)
+                } else {
+                    (end, *start)
+                };
+                if edges.insert(edge) {
+                    lines.lines.push([edge.0, edge.1]);
+                }
+            }
+        }
+        lines
+    }
+
+    /// The edges of `face_edge_lines` as flat ribbons `width` wide and raised `lift` above the
+    /// surface, for renderers that can't draw thick lines. Every ribbon is a face and runs
+    /// half its width past the ends of its edge so that ribbons overlap at the corners.
+    pub fn face_edge_ribbons(&self, width: f32, lift: f32) -> Polyhedron {
+        let lines = self.face_edge_lines();
+        let lifted: Vec<Vector3<f32>> = lines
+            .positions
+            .iter()
+            .map(|position| {
+                let distance = position.0.magnitude();
+                position.0 * (distance + lift) / distance
+            })
+            .collect();
+
+        let mut ribbons = Polyhedron::new();
+        let white = Vector3::new(1.0, 1.0, 1.0);
+        for [start, end] in lines.lines.iter() {
+            let up = (lifted[*start] + lifted[*end]).normalize();
+            let direction = (lifted[*end] - lifted[*start]).normalize();
+            let side = direction.cross(up).normalize() * (width / 2.0);
+            let start = lifted[*start] - direction * (width / 2.0);
+            let end = lifted[*end] + direction * (width / 2.0);
+            let normal = side.cross(direction).normalize();
+            let normal = if normal.dot(up) < 0.0 {
+                -normal
+            } else {
+                normal
+            };
+            let quad: Vec<usize> = [start - side, end - side, end + side, start + side]
+                .iter()
+                .map(|position| ribbons.push_vertex(*position, normal, white))
+                .collect();
+            ribbons.push_facing([quad[0], quad[1], quad[2]], normal);
+            ribbons.push_facing([quad[0], quad[2], quad[3]], normal);
+            let cell_count = ribbons.cells.len();
+            ribbons.faces.push(vec![cell_count - 2, cell_count - 1]);
+        }
+        ribbons
+    }
+}
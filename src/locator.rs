@@ -0,0 +1,165 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::lattice::{lattice_cell_index, BaseLattice};
+use crate::uv::direction_from_longitude_latitude;
+use crate::Polyhedron;
+
+/// Finds the face under a direction from the center of a polyhedron without testing every
+/// face.
+///
+/// Directions are bucketed by the triangle of the base icosahedron lattice they pass
+/// through, which gives a nearby face to walk from toward the face with the closest center.
+/// The faces around it are then tested exactly. Directions on an edge or corner shared by
+/// several faces give the face with the lowest index.
+pub struct FaceLocator {
+    centers: Vec<Vector3<f32>>,
+    /// Smallest dot product between a face's center and any of its corners, so directions
+    /// with a smaller one can't be on the face.
+    extents: Vec<f32>,
+    adjacency: Vec<Vec<usize>>,
+    triangles: Vec<Vec<[Vector3<f32>; 3]>>,
+    lattice: BaseLattice,
+    cols: usize,
+    seeds: Vec<usize>,
+}
+
+impl FaceLocator {
+    pub fn new(polyhedron: &Polyhedron) -> FaceLocator {
+        let face_count = polyhedron.faces.len();
+        let centers: Vec<Vector3<f32>> = (0..face_count)
+            .map(|i| polyhedron.face_center(i).normalize())
+            .collect();
+        let triangles: Vec<Vec<[Vector3<f32>; 3]>> = polyhedron
+            .faces
+            .iter()
+            .map(|face| {
+                face.iter()
+                    .map(|cell_index| {
+                        let cell = &polyhedron.cells[*cell_index];
+                        [
+                            polyhedron.positions[cell.a].0,
+                            polyhedron.positions[cell.b].0,
+                            polyhedron.positions[cell.c].0,
+                        ]
+                    })
+                    .collect()
+            })
+            .collect();
+        let extents = centers
+            .iter()
+            .zip(triangles.iter())
+            .map(|(center, face_triangles)| {
+                let extent = face_triangles
+                    .iter()
+                    .flat_map(|triangle| triangle.iter())
+                    .map(|corner| corner.normalize().dot(*center))
+                    .fold(1.0, f32::min);
+                // caps reaching past the equator aren't convex, so test those faces always
+                if extent <= 0.0 {
+                    -1.0
+                } else {
+                    extent - 1e-5
+                }
+            })
+            .collect();
+
+        // aim for about two faces per bucket
+        let mut cols = 1;
+        while 20 * (cols * 2) * (cols * 2) * 2 <= face_count {
+            cols *= 2;
+        }
+        let lattice = BaseLattice::new();
+        let mut seeds: Vec<Option<usize>> = vec![None; 20 * cols * cols];
+        for (face_index, center) in centers.iter().enumerate() {
+            let (base, weights) = lattice.locate(*center);
+            let (i, k, odd) = lattice.cell(weights, cols);
+            let bucket = lattice_cell_index(base, i, k, odd, cols);
+            if seeds[bucket].is_none() {
+                seeds[bucket] = Some(face_index);
+            }
+        }
+
+        FaceLocator {
+            centers,
+            extents,
+            adjacency: polyhedron.face_adjacency(),
+            triangles,
+            lattice,
+            cols,
+            seeds: seeds.iter().map(|seed| seed.unwrap_or(0)).collect(),
+        }
+    }
+
+    /// The face the direction passes through, or `None` if it misses the polyhedron, such as
+    /// a patch or chunk that only covers part of the sphere.
+    pub fn face_at(&self, direction: Vector3<f32>) -> Option<usize> {
+        if self.centers.is_empty() || direction.magnitude2() == 0.0 {
+            return None;
+        }
+        let direction = direction.normalize();
+        let (base, weights) = self.lattice.locate(direction);
+        let (i, k, odd) = self.lattice.cell(weights, self.cols);
+        let mut current = self.seeds[lattice_cell_index(base, i, k, odd, self.cols)];
+
+        loop {
+            let mut closest = current;
+            for neighbor in self.adjacency[current].iter() {
+                if self.centers[*neighbor].dot(direction) > self.centers[closest].dot(direction) {
+                    closest = *neighbor;
+                }
+            }
+            if closest == current {
+                break;
+            }
+            current = closest;
+        }
+
+        let mut candidates = vec![current];
+        candidates.extend(self.adjacency[current].iter());
+        for neighbor in self.adjacency[current].iter() {
+            candidates.extend(self.adjacency[*neighbor].iter());
+        }
+        candidates
+            .into_iter()
+            .filter(|face_index| self.face_contains(*face_index, direction))
+            .min()
+            .or_else(|| {
+                // the closest center isn't always next to the face the direction is on, so
+                // fall back to every face close enough to have it
+                (0..self.centers.len()).find(|face_index| {
+                    self.centers[*face_index].dot(direction) >= self.extents[*face_index]
+                        && self.face_contains(*face_index, direction)
+                })
+            })
+    }
+
+    /// Like `face_at`, with the direction given as a latitude and a longitude (around the y
+    /// axis, starting from the x axis) in radians.
+    pub fn face_at_latitude_longitude(&self, latitude: f32, longitude: f32) -> Option<usize> {
+        self.face_at(direction_from_longitude_latitude(longitude, latitude))
+    }
+
+    fn face_contains(&self, face_index: usize, direction: Vector3<f32>) -> bool {
+        self.triangles[face_index].iter().any(|[a, b, c]| {
+            let determinant = a.dot(b.cross(*c));
+            if determinant == 0.0 {
+                return false;
+            }
+            // rounding in the weights grows with the size of the sphere, not of the triangle
+            let tolerance = -1e-6 * a.magnitude2();
+            let weights = [
+                direction.dot(b.cross(*c)) * determinant.signum(),
+                a.dot(direction.cross(*c)) * determinant.signum(),
+                a.dot(b.cross(direction)) * determinant.signum(),
+            ];
+            weights.iter().all(|weight| *weight >= tolerance) && weights.iter().sum::<f32>() > 0.0
+        })
+    }
+}
+
+impl Polyhedron {
+    pub fn face_locator(&self) -> FaceLocator {
+        FaceLocator::new(self)
+    }
+}
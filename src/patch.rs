@@ -0,0 +1,210 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+
+use crate::{Polyhedron, Triangle};
+
+/// A triangle of the base icosahedron subdivided `path.len()` times, where each step picks
+/// one of the 4 smaller triangles: 0, 1 and 2 for the triangles at the first, second and
+/// third corner and 3 for the triangle in the middle.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Patch {
+    pub base_face: usize,
+    pub path: Vec<u8>,
+}
+
+impl Patch {
+    pub fn new(base_face: usize) -> Patch {
+        Patch {
+            base_face,
+            path: vec![],
+        }
+    }
+
+    pub fn level(&self) -> u32 {
+        self.path.len() as u32
+    }
+
+    pub fn child(&self, quadrant: u8) -> Patch {
+        let mut path = self.path.clone();
+        path.push(quadrant);
+        Patch {
+            base_face: self.base_face,
+            path,
+        }
+    }
+
+    pub fn children(&self) -> [Patch; 4] {
+        [self.child(0), self.child(1), self.child(2), self.child(3)]
+    }
+
+    pub fn parent(&self) -> Option<Patch> {
+        if self.path.is_empty() {
+            return None;
+        }
+        Some(Patch {
+            base_face: self.base_face,
+            path: self.path[..self.path.len() - 1].to_vec(),
+        })
+    }
+
+    /// Corners of the patch on the flat base icosahedron triangle, in the same order as the
+    /// base triangle's corners.
+    pub fn corners(&self) -> [Vector3<f32>; 3] {
+        let base = Polyhedron::base_isocahedron();
+        let cell = &base.cells[self.base_face];
+        let mut corners = [
+            base.positions[cell.a].0,
+            base.positions[cell.b].0,
+            base.positions[cell.c].0,
+        ];
+        for quadrant in self.path.iter() {
+            let [a, b, c] = corners;
+            let mid_ab = a.lerp(b, 0.5);
+            let mid_bc = b.lerp(c, 0.5);
+            let mid_ca = c.lerp(a, 0.5);
+            corners = match quadrant {
+                0 => [a, mid_ab, mid_ca],
+                1 => [mid_ab, b, mid_bc],
+                2 => [mid_ca, mid_bc, c],
+                _ => [mid_bc, mid_ca, mid_ab],
+            };
+        }
+        corners
+    }
+}
+
+impl Polyhedron {
+    /// Subdivides a patch of the base icosahedron like `new_isocahedron` does, so patches at
+    /// the same level and detail line up with each other and with `new_isocahedron(radius,
+    /// patch.level() + detail)`. Every triangle is a face.
+    pub fn new_patch(radius: f32, patch: &Patch, detail: u32) -> Polyhedron {
+        let level = patch.level() + detail;
+        Polyhedron::new_stitched_patch(radius, patch, detail, [level; 3], 0.0)
+    }
+
+    /// Like `new_patch`, but moves the vertices along each edge onto the edges of a
+    /// neighboring patch with a coarser lattice so the two meet without cracks.
+    ///
+    /// `edge_levels` are the lattice levels (`patch.level() + detail`) of the neighbors across
+    /// the edges from the first to the second corner, the second to the third and the third
+    /// to the first. Neighbors at the same or a finer level leave the edge as it is. When
+    /// `skirt_depth` is above 0, a skirt reaching that far down toward the center is added
+    /// along every edge as 3 more faces after the patch's triangles.
+    ///
+    /// Panics if an edge level is below `patch.level()`: the corners of the patch aren't
+    /// vertices of such a coarse neighbor, so its edge can't be matched by moving only the
+    /// vertices between them.
+    pub fn new_stitched_patch(
+        radius: f32,
+        patch: &Patch,
+        detail: u32,
+        edge_levels: [u32; 3],
+        skirt_depth: f32,
+    ) -> Polyhedron {
+        let [a, b, c] = patch.corners();
+        let cols = 2usize.pow(detail);
+        let level = patch.level() + detail;
+        assert!(
+            edge_levels
+                .iter()
+                .all(|edge_level| *edge_level >= patch.level()),
+            "Edge levels {:?} are coarser than the patch's level {}",
+            edge_levels,
+            patch.level()
+        );
+        let lattice_point = |i: usize, k: usize| -> Vector3<f32> {
+            let aj = a.lerp(c, i as f32 / cols as f32);
+            let bj = b.lerp(c, i as f32 / cols as f32);
+            let rows = cols - i;
+            if rows == 0 {
+                aj.normalize() * radius
+            } else {
+                aj.lerp(bj, k as f32 / rows as f32).normalize() * radius
+            }
+        };
+
+        // lattice coordinates of the vertices along each edge, in counter-clockwise order
+        let edges: [Vec<(usize, usize)>; 3] = [
+            (0..=cols).map(|p| (0, p)).collect(),
+            (0..=cols).map(|p| (p, cols - p)).collect(),
+            (0..=cols).map(|p| (cols - p, 0)).collect(),
+        ];
+
+        let mut vertices: Vec<Vec<Vector3<f32>>> = (0..=cols)
+            .map(|i| (0..=cols - i).map(|k| lattice_point(i, k)).collect())
+            .collect();
+        for (edge, edge_level) in edges.iter().zip(edge_levels.iter()) {
+            if *edge_level >= level {
+                continue;
+            }
+            let step = 2usize.pow(level - edge_level);
+            for p in 0..=cols {
+                if p % step == 0 {
+                    continue;
+                }
+                let lower = p / step * step;
+                let (lower_i, lower_k) = edge[lower];
+                let (upper_i, upper_k) = edge[lower + step];
+                let (i, k) = edge[p];
+                vertices[i][k] = lattice_point(lower_i, lower_k).lerp(
+                    lattice_point(upper_i, upper_k),
+                    (p - lower) as f32 / step as f32,
+                );
+            }
+        }
+
+        let mut polyhedron = Polyhedron::new();
+        for i in 0..cols {
+            for j in 0..2 * (cols - i) - 1 {
+                let k = j / 2;
+
+                let mut triangle = Triangle { a: 0, b: 0, c: 0 };
+                if j % 2 == 0 {
+                    triangle.a = polyhedron.add_position(vertices[i][k + 1]);
+                    triangle.b = polyhedron.add_position(vertices[i + 1][k]);
+                    triangle.c = polyhedron.add_position(vertices[i][k]);
+                } else {
+                    triangle.a = polyhedron.add_position(vertices[i][k + 1]);
+                    triangle.b = polyhedron.add_position(vertices[i + 1][k + 1]);
+                    triangle.c = polyhedron.add_position(vertices[i + 1][k]);
+                }
+
+                polyhedron.cells.push(triangle);
+            }
+        }
+        polyhedron.triangles_to_faces();
+
+        if skirt_depth > 0.0 {
+            for edge in edges.iter() {
+                let mut skirt_face = vec![];
+                for pair in edge.windows(2) {
+                    let (start_i, start_k) = pair[0];
+                    let (end_i, end_k) = pair[1];
+                    let start = vertices[start_i][start_k];
+                    let end = vertices[end_i][end_k];
+                    let start_index = polyhedron.add_position(start);
+                    let end_index = polyhedron.add_position(end);
+                    let lowered_start_index = polyhedron
+                        .add_position(start.normalize() * (start.magnitude() - skirt_depth));
+                    let lowered_end_index =
+                        polyhedron.add_position(end.normalize() * (end.magnitude() - skirt_depth));
+
+                    polyhedron.cells.push(Triangle::new(
+                        start_index,
+                        lowered_start_index,
+                        end_index,
+                    ));
+                    skirt_face.push(polyhedron.cells.len() - 1);
+                    polyhedron.cells.push(Triangle::new(
+                        end_index,
+                        lowered_start_index,
+                        lowered_end_index,
+                    ));
+                    skirt_face.push(polyhedron.cells.len() - 1);
+                }
+                polyhedron.faces.push(skirt_face);
+            }
+        }
+        polyhedron
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BinaryHeap;
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::index::sample;
+
+use crate::graph::QueueEntry;
+use crate::Polyhedron;
+
+/// A group of neighboring faces that move together.
+#[derive(Debug, Clone)]
+pub struct Plate {
+    /// Face the plate grew from.
+    pub center: usize,
+    /// How fast the plate grew compared to the others. Heavier plates end up bigger.
+    pub weight: f32,
+    /// Axis the plate rotates around through the center of the polyhedron, scaled by its
+    /// angular speed in radians per unit of time.
+    pub rotation: Vector3<f32>,
+}
+
+impl Plate {
+    /// Velocity of the plate at a point on it.
+    pub fn velocity(&self, point: Vector3<f32>) -> Vector3<f32> {
+        self.rotation.cross(point)
+    }
+}
+
+/// How two plates move relative to each other along their boundary.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoundaryKind {
+    /// Moving toward each other, raising mountains or trenches.
+    Convergent,
+    /// Moving apart, opening rifts or ridges.
+    Divergent,
+    /// Sliding past each other.
+    Transform,
+}
+
+/// An edge between two faces on different plates.
+#[derive(Debug, Clone)]
+pub struct PlateBoundary {
+    pub faces: (usize, usize),
+    pub plates: (usize, usize),
+    pub kind: BoundaryKind,
+    /// Speed the plates move toward each other across the edge, negative when they move
+    /// apart.
+    pub convergence: f32,
+    /// Speed the plates slide past each other along the edge.
+    pub shear: f32,
+}
+
+#[derive(Debug, Clone)]
+pub struct Plates {
+    pub plates: Vec<Plate>,
+    /// Index of the plate every face is on, or `usize::MAX` for faces no plate could reach.
+    pub face_plates: Vec<usize>,
+    /// Every edge between faces on different plates, once.
+    pub boundaries: Vec<PlateBoundary>,
+}
+
+impl Polyhedron {
+    /// Splits the faces into `count` plates grown outward from random faces, with random
+    /// weights and rotations. The same seed always gives the same plates.
+    ///
+    /// Plates grow across the face adjacency graph like a weighted Voronoi diagram, where the
+    /// cost of crossing into a face is jittered per face so that plate outlines are ragged.
+    pub fn generate_plates(&self, count: usize, seed: u64) -> Plates {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let graph = self.tile_graph();
+        let face_count = graph.face_count();
+        let count = count.clamp(1, face_count.max(1));
+        let plates: Vec<Plate> = sample(&mut rng, face_count, count)
+            .into_iter()
+            .map(|center| {
+                let axis = Vector3::new(
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                    rng.gen_range(-1.0, 1.0),
+                );
+                let axis = if axis.magnitude2() > 0.0 {
+                    axis.normalize()
+                } else {
+                    Vector3::unit_y()
+                };
+                Plate {
+                    center,
+                    weight: rng.gen_range(0.5, 1.5),
+                    rotation: axis * rng.gen_range(0.0, 1.0),
+                }
+            })
+            .collect();
+        let roughness: Vec<f32> = (0..face_count).map(|_| rng.gen_range(0.5, 1.5)).collect();
+
+        let mut costs = vec![f32::INFINITY; face_count];
+        let mut face_plates = vec![usize::MAX; face_count];
+        let mut open = BinaryHeap::new();
+        for (plate_index, plate) in plates.iter().enumerate() {
+            costs[plate.center] = 0.0;
+            face_plates[plate.center] = plate_index;
+            open.push(QueueEntry {
+                estimate: 0.0,
+                face: plate.center,
+            });
+        }
+        while let Some(QueueEntry { estimate, face }) = open.pop() {
+            if estimate > costs[face] {
+                continue;
+            }
+            let plate = &plates[face_plates[face]];
+            for neighbor in graph.neighbors(face).iter() {
+                let cost = estimate
+                    + graph.great_circle_distance(face, *neighbor) * roughness[*neighbor]
+                        / plate.weight;
+                if cost < costs[*neighbor] {
+                    costs[*neighbor] = cost;
+                    face_plates[*neighbor] = face_plates[face];
+                    open.push(QueueEntry {
+                        estimate: cost,
+                        face: *neighbor,
+                    });
+                }
+            }
+        }
+
+        let centers: Vec<Vector3<f32>> = (0..face_count)
+            .map(|i| self.face_center(i).normalize())
+            .collect();
+        let mut boundaries = vec![];
+        for face in 0..face_count {
+            for neighbor in graph.neighbors(face).iter() {
+                let (plate, other_plate) = (face_plates[face], face_plates[*neighbor]);
+                if *neighbor < face || plate == other_plate || other_plate == usize::MAX {
+                    continue;
+                }
+                let point = (centers[face] + centers[*neighbor]).normalize();
+                let across = centers[*neighbor] - centers[face];
+                let across = (across - point * across.dot(point)).normalize();
+                let along = point.cross(across);
+                let relative = plates[plate].velocity(point) - plates[other_plate].velocity(point);
+                let convergence = relative.dot(across);
+                let shear = relative.dot(along).abs();
+                let kind = if convergence.abs() < shear {
+                    BoundaryKind::Transform
+                } else if convergence > 0.0 {
+                    BoundaryKind::Convergent
+                } else {
+                    BoundaryKind::Divergent
+                };
+                boundaries.push(PlateBoundary {
+                    faces: (face, *neighbor),
+                    plates: (plate, other_plate),
+                    kind,
+                    convergence,
+                    shear,
+                });
+            }
+        }
+
+        Plates {
+            plates,
+            face_plates,
+            boundaries,
+        }
+    }
+}
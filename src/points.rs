@@ -0,0 +1,177 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use cgmath::prelude::*;
+use cgmath::Vector3;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::Polyhedron;
+
+/// Candidates tried around every point before giving up on it in `PoissonDisk`.
+const POISSON_DISK_CANDIDATES: usize = 30;
+/// Share of the sphere's area per point, measured in squared `min_angle`, that a filled
+/// `PoissonDisk` ends up with.
+const POISSON_DISK_DENSITY: f32 = 0.624;
+
+/// Ways of scattering points over the unit sphere, for `new_spherical_delaunay` and
+/// `new_spherical_voronoi`. The same seed always gives the same points.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PointDistribution {
+    /// Evenly spaced points along a spiral from pole to pole.
+    Fibonacci { count: usize },
+    /// Random points that are all at least `min_angle` radians apart, filled in until there
+    /// is no room for more.
+    PoissonDisk { min_angle: f32, seed: u64 },
+    /// The vertices of `new_isocahedron` each moved in a random direction by up to `jitter`
+    /// times the length of an edge.
+    JitteredIsocahedron { detail: u32, jitter: f32, seed: u64 },
+}
+
+impl PointDistribution {
+    /// `PoissonDisk` with the `min_angle` that gives roughly `count` points.
+    pub fn poisson_disk_with_count(count: usize, seed: u64) -> PointDistribution {
+        PointDistribution::PoissonDisk {
+            min_angle: (POISSON_DISK_DENSITY * 4.0 * PI / count.max(1) as f32).sqrt(),
+            seed,
+        }
+    }
+
+    pub fn points(&self) -> Vec<Vector3<f32>> {
+        match *self {
+            PointDistribution::Fibonacci { count } => fibonacci_points(count),
+            PointDistribution::PoissonDisk { min_angle, seed } => {
+                poisson_disk_points(min_angle, seed)
+            }
+            PointDistribution::JitteredIsocahedron {
+                detail,
+                jitter,
+                seed,
+            } => jittered_isocahedron_points(detail, jitter, seed),
+        }
+    }
+}
+
+fn fibonacci_points(count: usize) -> Vec<Vector3<f32>> {
+    let golden_angle = PI * (3.0 - 5.0_f32.sqrt());
+    (0..count)
+        .map(|i| {
+            let y = 1.0 - 2.0 * (i as f32 + 0.5) / count as f32;
+            let ring_radius = (1.0 - y * y).sqrt();
+            let angle = golden_angle * i as f32;
+            Vector3::new(ring_radius * angle.cos(), y, ring_radius * angle.sin())
+        })
+        .collect()
+}
+
+/// Bridson's algorithm on the sphere, with points bucketed in a grid of cubes twice as wide as
+/// the minimum distance, so only the 8 cubes on the candidate's side of its own cube can hold
+/// points too close to it.
+fn poisson_disk_points(min_angle: f32, seed: u64) -> Vec<Vector3<f32>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let min_angle = min_angle.clamp(1e-4, PI);
+    let min_distance = 2.0 * (min_angle / 2.0).sin();
+    let cell_size = 2.0 * min_distance;
+    let cell = |point: Vector3<f32>| {
+        (
+            (point.x / cell_size).floor() as i32,
+            (point.y / cell_size).floor() as i32,
+            (point.z / cell_size).floor() as i32,
+        )
+    };
+    // the cube next to a cell on the side of its middle that a coordinate is on
+    let side = |value: f32, cell: i32| {
+        if value / cell_size - (cell as f32) < 0.5 {
+            cell - 1
+        } else {
+            cell + 1
+        }
+    };
+
+    let first = random_direction(&mut rng);
+    let mut points = vec![first];
+    let mut grid: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+    grid.insert(cell(first), vec![0]);
+    let mut active = vec![0];
+    while !active.is_empty() {
+        let active_index = rng.gen_range(0, active.len());
+        let point = points[active[active_index]];
+        let reference = if point.x.abs() < 0.9 {
+            Vector3::unit_x()
+        } else {
+            Vector3::unit_y()
+        };
+        let u = point.cross(reference).normalize();
+        let v = point.cross(u);
+
+        let mut found = false;
+        for _ in 0..POISSON_DISK_CANDIDATES {
+            let angle = rng.gen_range(min_angle, 2.0 * min_angle);
+            let azimuth = rng.gen_range(0.0, 2.0 * PI);
+            let candidate = (point * angle.cos()
+                + (u * azimuth.cos() + v * azimuth.sin()) * angle.sin())
+            .normalize();
+            let (x, y, z) = cell(candidate);
+            let near = (
+                side(candidate.x, x),
+                side(candidate.y, y),
+                side(candidate.z, z),
+            );
+            let mut fits = true;
+            'search: for nx in [x, near.0].iter() {
+                for ny in [y, near.1].iter() {
+                    for nz in [z, near.2].iter() {
+                        if let Some(others) = grid.get(&(*nx, *ny, *nz)) {
+                            if others.iter().any(|other| {
+                                (points[*other] - candidate).magnitude2()
+                                    < min_distance * min_distance
+                            }) {
+                                fits = false;
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+            }
+            if fits {
+                points.push(candidate);
+                grid.entry((x, y, z)).or_default().push(points.len() - 1);
+                active.push(points.len() - 1);
+                found = true;
+                break;
+            }
+        }
+        if !found {
+            active.swap_remove(active_index);
+        }
+    }
+    points
+}
+
+fn jittered_isocahedron_points(detail: u32, jitter: f32, seed: u64) -> Vec<Vector3<f32>> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    // angle between the corners of the base icosahedron, halved by every detail level
+    let edge_angle = 1.107_148_8 / 2.0_f32.powi(detail as i32);
+    Polyhedron::new_isocahedron(1.0, detail)
+        .positions
+        .iter()
+        .map(|position| {
+            let point = position.0.normalize();
+            let offset = random_direction(&mut rng);
+            let tangent = offset - point * offset.dot(point);
+            if tangent.magnitude2() == 0.0 {
+                return point;
+            }
+            // uniform over a disk around the point
+            let distance = edge_angle * jitter * rng.gen::<f32>().sqrt();
+            (point + tangent.normalize() * distance.tan()).normalize()
+        })
+        .collect()
+}
+
+fn random_direction(rng: &mut StdRng) -> Vector3<f32> {
+    let y: f32 = rng.gen_range(-1.0, 1.0);
+    let angle: f32 = rng.gen_range(0.0, 2.0 * PI);
+    let ring_radius = (1.0 - y * y).sqrt();
+    Vector3::new(ring_radius * angle.cos(), y, ring_radius * angle.sin())
+}
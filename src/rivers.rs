@@ -0,0 +1,173 @@
+use std::collections::BinaryHeap;
+
+use cgmath::Vector3;
+
+use crate::graph::QueueEntry;
+use crate::Polyhedron;
+
+/// Smallest height every face is raised above the face it drains into when depressions are
+/// filled, so that water on a filled lake still has somewhere downhill to go.
+const FILL_EPSILON: f32 = 1e-5;
+/// Multiple of the elevation's own precision that the fill step grows to on high terrain,
+/// where `FILL_EPSILON` would be rounded away.
+const FILL_RELATIVE_EPSILON: f32 = 4.0 * f32::EPSILON;
+
+/// Where water flows across the faces of a polyhedron.
+#[derive(Debug, Clone)]
+pub struct Drainage {
+    /// `face_elevations` with every depression that doesn't drain into the sea filled up to
+    /// the height where it overflows.
+    pub filled_elevations: Vec<f32>,
+    /// The neighbor each face drains into, down the steepest slope of the filled
+    /// elevations, or `None` for faces in the sea.
+    pub flow_directions: Vec<Option<usize>>,
+    /// Number of faces that drain through each face, including itself.
+    pub flow_accumulation: Vec<f32>,
+}
+
+/// A river as the faces it flows through, from its source to the face it ends in, which is
+/// either in the sea or on a bigger river.
+#[derive(Debug, Clone)]
+pub struct River {
+    pub faces: Vec<usize>,
+    /// Centers of `faces`, for drawing the river as a line.
+    pub points: Vec<Vector3<f32>>,
+}
+
+impl Polyhedron {
+    /// Works out how water drains over `face_elevations` into the faces below `sea_level`.
+    ///
+    /// Depressions are filled with a priority flood from the sea, or from the lowest face if
+    /// there is no sea, and then every face drains into its steepest downhill neighbor.
+    pub fn compute_drainage(&self, sea_level: f32) -> Drainage {
+        let graph = self.tile_graph();
+        let face_count = graph.face_count();
+        let elevations: Vec<f32> = (0..face_count)
+            .map(|i| self.face_elevations.get(i).cloned().unwrap_or(0.0))
+            .collect();
+
+        let mut filled_elevations = elevations.clone();
+        let mut flooded = vec![false; face_count];
+        let mut open = BinaryHeap::new();
+        let mut sea: Vec<usize> = (0..face_count)
+            .filter(|i| elevations[*i] < sea_level)
+            .collect();
+        if sea.is_empty() && face_count > 0 {
+            let lowest = (0..face_count)
+                .min_by(|a, b| elevations[*a].partial_cmp(&elevations[*b]).unwrap())
+                .unwrap();
+            sea.push(lowest);
+        }
+        for face in sea.iter() {
+            flooded[*face] = true;
+            open.push(QueueEntry {
+                estimate: elevations[*face],
+                face: *face,
+            });
+        }
+        while let Some(QueueEntry { estimate, face }) = open.pop() {
+            for neighbor in graph.neighbors(face).iter() {
+                if flooded[*neighbor] {
+                    continue;
+                }
+                flooded[*neighbor] = true;
+                filled_elevations[*neighbor] = elevations[*neighbor].max(raised_above(estimate));
+                open.push(QueueEntry {
+                    estimate: filled_elevations[*neighbor],
+                    face: *neighbor,
+                });
+            }
+        }
+
+        let mut is_sea = vec![false; face_count];
+        for face in sea {
+            is_sea[face] = true;
+        }
+        let flow_directions: Vec<Option<usize>> = (0..face_count)
+            .map(|face| {
+                if is_sea[face] {
+                    return None;
+                }
+                let mut steepest: Option<(usize, f32)> = None;
+                for neighbor in graph.neighbors(face).iter() {
+                    let drop = filled_elevations[face] - filled_elevations[*neighbor];
+                    if drop <= 0.0 {
+                        continue;
+                    }
+                    let slope = drop / graph.great_circle_distance(face, *neighbor);
+                    match steepest {
+                        Some((_, steepest_slope)) if steepest_slope >= slope => {}
+                        _ => steepest = Some((*neighbor, slope)),
+                    }
+                }
+                steepest.map(|(neighbor, _)| neighbor)
+            })
+            .collect();
+
+        let mut order: Vec<usize> = (0..face_count).collect();
+        order.sort_by(|a, b| {
+            filled_elevations[*b]
+                .partial_cmp(&filled_elevations[*a])
+                .unwrap()
+        });
+        let mut flow_accumulation = vec![1.0; face_count];
+        for face in order {
+            if let Some(downstream) = flow_directions[face] {
+                flow_accumulation[downstream] += flow_accumulation[face];
+            }
+        }
+
+        Drainage {
+            filled_elevations,
+            flow_directions,
+            flow_accumulation,
+        }
+    }
+
+    /// Rivers through every face with at least `min_accumulation` faces draining through it.
+    /// Every river starts where the flow first reaches that amount and ends where it flows
+    /// into the sea or into a river carrying more water, so tributaries end on the face they
+    /// join.
+    pub fn extract_rivers(&self, drainage: &Drainage, min_accumulation: f32) -> Vec<River> {
+        let face_count = drainage.flow_directions.len();
+        let is_river = |face: usize| {
+            drainage.flow_directions[face].is_some()
+                && drainage.flow_accumulation[face] >= min_accumulation
+        };
+        // where rivers meet, the one carrying the most water keeps going
+        let mut main_upstream: Vec<Option<usize>> = vec![None; face_count];
+        for face in (0..face_count).filter(|face| is_river(*face)) {
+            if let Some(downstream) = drainage.flow_directions[face] {
+                match main_upstream[downstream] {
+                    Some(main)
+                        if drainage.flow_accumulation[main] >= drainage.flow_accumulation[face] => {
+                    }
+                    _ => main_upstream[downstream] = Some(face),
+                }
+            }
+        }
+
+        let mut rivers = vec![];
+        for source in
+            (0..face_count).filter(|face| is_river(*face) && main_upstream[*face].is_none())
+        {
+            let mut faces = vec![source];
+            let mut face = source;
+            while let Some(downstream) = drainage.flow_directions[face] {
+                faces.push(downstream);
+                if !is_river(downstream) || main_upstream[downstream] != Some(face) {
+                    break;
+                }
+                face = downstream;
+            }
+            let points = faces.iter().map(|face| self.face_center(*face)).collect();
+            rivers.push(River { faces, points });
+        }
+        rivers
+    }
+}
+
+/// The next height a filled face can drain down from to a face at `elevation`.
+fn raised_above(elevation: f32) -> f32 {
+    elevation + (elevation.abs() * FILL_RELATIVE_EPSILON).max(FILL_EPSILON)
+}
@@ -0,0 +1,147 @@
+use std::collections::HashMap;
+
+use cgmath::prelude::*;
+use cgmath::{Vector3, Vector4};
+
+use crate::{ArraySerializedVector4, Polyhedron};
+
+impl Polyhedron {
+    /// Replaces `tangents` with a tangent for every vertex, with the handedness of the
+    /// bitangent in `w` so that `bitangent = cross(normal, tangent.xyz) * tangent.w`.
+    ///
+    /// When the polyhedron has `uvs`, the tangents follow the direction `u` increases in. As in
+    /// MikkTSpace, each triangle's tangent is projected onto the plane of the vertex normal,
+    /// normalized and weighted by the triangle's angle at the vertex, and vertices shared by
+    /// triangles with opposite handedness are split.
+    /// Otherwise they point east, along lines of latitude, with the bitangent pointing north.
+    pub fn compute_tangents(&mut self) {
+        if self.uvs.is_empty() {
+            self.tangents = (0..self.positions.len())
+                .map(|i| {
+                    let east = east_tangent(self.positions[i].0, self.normals[i].0);
+                    ArraySerializedVector4(east.extend(-1.0))
+                })
+                .collect();
+            return;
+        }
+
+        // tangents are summed separately for each handedness, so that vertices on mirrored
+        // texture seams can be split instead of averaging opposite bitangents
+        let mut sums: HashMap<(usize, bool), Vector3<f32>> = HashMap::new();
+        let mut corner_handedness: Vec<[bool; 3]> = Vec::with_capacity(self.cells.len());
+        for cell in self.cells.iter() {
+            let vertices = [cell.a, cell.b, cell.c];
+            let p = vertices.map(|vertex| self.positions[vertex].0);
+            let uv = vertices.map(|vertex| self.uvs[vertex].0);
+
+            let e1 = p[1] - p[0];
+            let e2 = p[2] - p[0];
+            let duv1 = uv[1] - uv[0];
+            let duv2 = uv[2] - uv[0];
+            let determinant = duv1.x * duv2.y - duv2.x * duv1.y;
+            let (tangent, bitangent) = if determinant.abs() < f32::EPSILON {
+                (Vector3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 0.0))
+            } else {
+                let r = 1.0 / determinant;
+                (
+                    (e1 * duv2.y - e2 * duv1.y) * r,
+                    (e2 * duv1.x - e1 * duv2.x) * r,
+                )
+            };
+
+            let mut handedness = [true; 3];
+            for k in 0..3 {
+                let normal = self.normals[vertices[k]].0;
+                handedness[k] = normal.cross(tangent).dot(bitangent) >= 0.0;
+                let projected = tangent - normal * normal.dot(tangent);
+                if projected.magnitude2() < f32::EPSILON {
+                    continue;
+                }
+                let angle = corner_angle(p[k], p[(k + 1) % 3], p[(k + 2) % 3]);
+                *sums
+                    .entry((vertices[k], handedness[k]))
+                    .or_insert_with(|| Vector3::new(0.0, 0.0, 0.0)) +=
+                    projected.normalize() * angle;
+            }
+            corner_handedness.push(handedness);
+        }
+
+        let mut split_vertices: HashMap<usize, usize> = HashMap::new();
+        for (cell_index, handedness) in corner_handedness.iter().enumerate() {
+            let cell = &self.cells[cell_index];
+            let mut vertices = [cell.a, cell.b, cell.c];
+            for (vertex, right_handed) in vertices.iter_mut().zip(handedness.iter()) {
+                let both =
+                    sums.contains_key(&(*vertex, true)) && sums.contains_key(&(*vertex, false));
+                if both && !right_handed {
+                    *vertex = match split_vertices.get(vertex) {
+                        Some(split_vertex) => *split_vertex,
+                        None => {
+                            let split_vertex = self.duplicate_vertex(*vertex);
+                            split_vertices.insert(*vertex, split_vertex);
+                            split_vertex
+                        }
+                    };
+                }
+            }
+            let cell = &mut self.cells[cell_index];
+            cell.a = vertices[0];
+            cell.b = vertices[1];
+            cell.c = vertices[2];
+        }
+        let mut vertex_tangents: Vec<Option<(Vector3<f32>, bool)>> =
+            vec![None; self.positions.len()];
+        for ((vertex, right_handed), tangent) in sums {
+            let vertex = match split_vertices.get(&vertex) {
+                Some(split_vertex) if !right_handed => *split_vertex,
+                _ => vertex,
+            };
+            vertex_tangents[vertex] = Some((tangent, right_handed));
+        }
+
+        self.tangents = (0..self.positions.len())
+            .map(|i| {
+                let normal = self.normals[i].0;
+                match vertex_tangents[i] {
+                    Some((tangent, right_handed)) if tangent.magnitude2() >= f32::EPSILON => {
+                        let tangent = (tangent - normal * normal.dot(tangent)).normalize();
+                        let handedness = if right_handed { 1.0 } else { -1.0 };
+                        ArraySerializedVector4(Vector4::new(
+                            tangent.x, tangent.y, tangent.z, handedness,
+                        ))
+                    }
+                    _ => {
+                        let east = east_tangent(self.positions[i].0, normal);
+                        ArraySerializedVector4(east.extend(-1.0))
+                    }
+                }
+            })
+            .collect();
+    }
+}
+
+/// Unit vector pointing east at a point, orthogonal to its normal.
+fn east_tangent(position: Vector3<f32>, normal: Vector3<f32>) -> Vector3<f32> {
+    let mut east = Vector3::new(-position.z, 0.0, position.x);
+    if east.magnitude2() < f32::EPSILON * position.magnitude2() {
+        // at the poles east is undefined, so pick any direction
+        east = Vector3::new(1.0, 0.0, 0.0);
+    }
+    let east = east - normal * normal.dot(east);
+    if east.magnitude2() < f32::EPSILON {
+        normal.cross(Vector3::new(0.0, 0.0, 1.0)).normalize()
+    } else {
+        east.normalize()
+    }
+}
+
+/// Angle of a triangle at `corner`, between the edges to `next` and `previous`.
+fn corner_angle(corner: Vector3<f32>, next: Vector3<f32>, previous: Vector3<f32>) -> f32 {
+    let a = next - corner;
+    let b = previous - corner;
+    let lengths = (a.magnitude2() * b.magnitude2()).sqrt();
+    if lengths < f32::EPSILON {
+        return 0.0;
+    }
+    (a.dot(b) / lengths).clamp(-1.0, 1.0).acos()
+}
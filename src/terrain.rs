@@ -0,0 +1,111 @@
+use cgmath::prelude::*;
+use cgmath::Vector3;
+use noise::{Fbm, MultiFractal, NoiseFn, OpenSimplex, Perlin, RidgedMulti, Seedable, Simplex};
+
+use crate::Polyhedron;
+
+/// Gradient noise that the octaves of terrain noise are made of.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseBasis {
+    Perlin,
+    Simplex,
+    OpenSimplex,
+}
+
+/// How the octaves of terrain noise are added up.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Fractal {
+    /// Fractal Brownian motion, for rolling hills and continents.
+    Fbm,
+    /// Ridged multifractal, for sharp mountain ranges.
+    Ridged,
+}
+
+/// Settings for fractal 3D noise sampled on the unit sphere, so the same settings give the
+/// same terrain at any radius and detail level.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TerrainNoise {
+    pub seed: u32,
+    pub basis: NoiseBasis,
+    pub fractal: Fractal,
+    pub octaves: usize,
+    /// Frequency of the first octave, in cycles per unit of distance on the unit sphere.
+    pub frequency: f64,
+    /// How much the frequency grows with each octave.
+    pub lacunarity: f64,
+    /// How much the amplitude shrinks with each octave.
+    pub persistence: f64,
+    /// Scale of the elevations, which otherwise fall roughly in `-1.0..=1.0`.
+    pub amplitude: f32,
+}
+
+impl Default for TerrainNoise {
+    fn default() -> TerrainNoise {
+        TerrainNoise {
+            seed: 0,
+            basis: NoiseBasis::Perlin,
+            fractal: Fractal::Fbm,
+            octaves: 6,
+            frequency: 1.0,
+            lacunarity: 2.0,
+            persistence: 0.5,
+            amplitude: 1.0,
+        }
+    }
+}
+
+impl TerrainNoise {
+    /// Elevation at each of the given directions from the center of the sphere.
+    pub fn sample(&self, directions: &[Vector3<f32>]) -> Vec<f32> {
+        let noise = match self.basis {
+            NoiseBasis::Perlin => self.fractal_noise::<Perlin>(),
+            NoiseBasis::Simplex => self.fractal_noise::<Simplex>(),
+            NoiseBasis::OpenSimplex => self.fractal_noise::<OpenSimplex>(),
+        };
+        directions
+            .iter()
+            .map(|direction| {
+                let point = direction.normalize();
+                noise.get([point.x as f64, point.y as f64, point.z as f64]) as f32 * self.amplitude
+            })
+            .collect()
+    }
+
+    fn fractal_noise<T>(&self) -> Box<dyn NoiseFn<f64, 3>>
+    where
+        T: Default + Seedable + NoiseFn<f64, 3> + 'static,
+    {
+        match self.fractal {
+            Fractal::Fbm => Box::new(
+                Fbm::<T>::new(self.seed)
+                    .set_octaves(self.octaves)
+                    .set_frequency(self.frequency)
+                    .set_lacunarity(self.lacunarity)
+                    .set_persistence(self.persistence),
+            ),
+            Fractal::Ridged => Box::new(
+                RidgedMulti::<T>::new(self.seed)
+                    .set_octaves(self.octaves)
+                    .set_frequency(self.frequency)
+                    .set_lacunarity(self.lacunarity)
+                    .set_persistence(self.persistence),
+            ),
+        }
+    }
+}
+
+impl Polyhedron {
+    /// Fills `face_elevations` with the terrain noise at the center of each face.
+    pub fn assign_face_elevations(&mut self, terrain: &TerrainNoise) {
+        let centers: Vec<Vector3<f32>> =
+            (0..self.faces.len()).map(|i| self.face_center(i)).collect();
+        self.face_elevations = terrain.sample(&centers);
+    }
+
+    /// Fills `vertex_elevations` with the terrain noise at each vertex.
+    pub fn assign_vertex_elevations(&mut self, terrain: &TerrainNoise) {
+        let positions: Vec<Vector3<f32>> =
+            self.positions.iter().map(|position| position.0).collect();
+        self.vertex_elevations = terrain.sample(&positions);
+    }
+}
@@ -0,0 +1,315 @@
+use std::collections::HashSet;
+
+use crate::grid::{HexGrid, TileCoord};
+use crate::lattice::{BaseLattice, LATTICE_TOLERANCE};
+use crate::patch::Patch;
+use crate::Polyhedron;
+
+const HEX_FLAG: u64 = 1 << 63;
+const BASE_SHIFT: u32 = 58;
+const BASE_MASK: u64 = 0x1f;
+const HEX_LEVEL_SHIFT: u32 = 52;
+const HEX_LEVEL_MASK: u64 = 0x3f;
+const HEX_Q_SHIFT: u32 = 26;
+const HEX_AXIS_MASK: u64 = (1 << 26) - 1;
+/// Deepest level a `TileId` can describe.
+pub const MAX_TILE_ID_LEVEL: u32 = 25;
+
+/// An identifier for a face of a polyhedron made by `new_isocahedron` or
+/// `new_truncated_isocahedron` that only depends on where the face is, not on the order faces
+/// were generated in. The layout of the bits is part of the crate's stable interface.
+///
+/// Triangles (bit 63 clear) are `Patch`es:
+///
+/// - bits 58-62: the base icosahedron triangle
+/// - below that, 2 bits for each step of the patch's path, first step highest, followed by a
+///   single set bit and zeros
+///
+/// Hexsphere tiles (bit 63 set) are the lattice points at their centers, at the coarsest
+/// level the point exists at, so a tile keeps its ID at every finer detail level:
+///
+/// - bits 58-62: the base icosahedron triangle of its `TileCoord`
+/// - bits 52-57: the level the tile first appears at, where level 0 is the 12 pentagons
+/// - bits 26-51: `q` at that level
+/// - bits 0-25: `r` at that level
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TileId(pub u64);
+
+impl TileId {
+    pub fn from_patch(patch: &Patch) -> TileId {
+        assert!(patch.level() <= MAX_TILE_ID_LEVEL, "Patch is too deep");
+        let mut id = (patch.base_face as u64) << BASE_SHIFT;
+        let mut shift = BASE_SHIFT;
+        for quadrant in patch.path.iter() {
+            shift -= 2;
+            id |= (*quadrant as u64 & 3) << shift;
+        }
+        TileId(id | 1 << (shift - 1))
+    }
+
+    /// The patch of a triangle ID, or `None` for a hexsphere tile.
+    pub fn patch(self) -> Option<Patch> {
+        if self.is_hex() || self.0.trailing_zeros() >= BASE_SHIFT {
+            return None;
+        }
+        let level = (BASE_SHIFT - 1 - self.0.trailing_zeros()) / 2;
+        let path = (0..level)
+            .map(|step| ((self.0 >> (BASE_SHIFT - 2 * (step + 1))) & 3) as u8)
+            .collect();
+        Some(Patch {
+            base_face: self.base(),
+            path,
+        })
+    }
+
+    /// The ID of a tile on a hexsphere with `size` subdivisions per base edge, which must be a
+    /// power of two. The coordinate must be normalized, or the same tile could get a different
+    /// ID from each base triangle it's on, so outside the crate IDs come from
+    /// `HexGrid::tile_id`.
+    pub(crate) fn from_coord(coord: TileCoord, size: i32) -> TileId {
+        assert!(
+            size > 0 && (size as u32).is_power_of_two(),
+            "Hexsphere size isn't a power of two"
+        );
+        assert!(
+            coord.base < 20 && coord.q >= 0 && coord.r >= 0 && coord.q + coord.r <= size,
+            "Tile coordinate {:?} is outside its base triangle",
+            coord
+        );
+        let mut level = size.trailing_zeros();
+        assert!(level <= MAX_TILE_ID_LEVEL, "Hexsphere is too detailed");
+        let (mut q, mut r) = (coord.q, coord.r);
+        while level > 0 && q % 2 == 0 && r % 2 == 0 {
+            q /= 2;
+            r /= 2;
+            level -= 1;
+        }
+        TileId(
+            HEX_FLAG
+                | (coord.base as u64) << BASE_SHIFT
+                | (level as u64) << HEX_LEVEL_SHIFT
+                | (q as u64) << HEX_Q_SHIFT
+                | r as u64,
+        )
+    }
+
+    /// The coordinate of a hexsphere tile ID on a hexsphere with `size` subdivisions per base
+    /// edge, or `None` for a triangle or a tile that only appears at finer levels.
+    pub fn coord(self, size: i32) -> Option<TileCoord> {
+        if !self.is_hex() || size <= 0 || !(size as u32).is_power_of_two() {
+            return None;
+        }
+        let level = self.level();
+        let size_level = size.trailing_zeros();
+        if level > size_level {
+            return None;
+        }
+        let scale = 1 << (size_level - level);
+        Some(TileCoord {
+            base: self.base(),
+            q: ((self.0 >> HEX_Q_SHIFT) & HEX_AXIS_MASK) as i32 * scale,
+            r: (self.0 & HEX_AXIS_MASK) as i32 * scale,
+        })
+    }
+
+    pub fn is_hex(self) -> bool {
+        self.0 & HEX_FLAG != 0
+    }
+
+    /// The base icosahedron triangle the tile is in.
+    pub fn base(self) -> usize {
+        ((self.0 >> BASE_SHIFT) & BASE_MASK) as usize
+    }
+
+    /// The patch level of a triangle, or the level a hexsphere tile first appears at.
+    pub fn level(self) -> u32 {
+        if self.is_hex() {
+            ((self.0 >> HEX_LEVEL_SHIFT) & HEX_LEVEL_MASK) as u32
+        } else {
+            (BASE_SHIFT - 1 - self.0.trailing_zeros().min(BASE_SHIFT - 1)) / 2
+        }
+    }
+}
+
+impl HexGrid {
+    pub fn tile_id(&self, face_index: usize) -> TileId {
+        TileId::from_coord(self.coord(face_index), self.size())
+    }
+
+    pub fn face_by_tile_id(&self, id: TileId) -> Option<usize> {
+        self.face(id.coord(self.size())?)
+    }
+}
+
+impl Polyhedron {
+    /// The `TileId` of every face of a polyhedron made by `new_isocahedron` or
+    /// `new_truncated_isocahedron`. Panics for other polyhedrons.
+    pub fn tile_ids(&self) -> Vec<TileId> {
+        let face_count = self.faces.len();
+        if !face_count.is_multiple_of(20) {
+            let grid = self.hex_grid();
+            return (0..face_count).map(|i| grid.tile_id(i)).collect();
+        }
+
+        let cols = ((face_count / 20) as f64).sqrt().round() as usize;
+        assert!(
+            cols.is_power_of_two() && 20 * cols * cols == face_count,
+            "Polyhedron isn't a subdivided icosahedron"
+        );
+        let lattice = BaseLattice::new();
+        let mut seen = HashSet::new();
+        (0..face_count)
+            .map(|face_index| {
+                let (base, weights) = lattice.locate(self.face_center(face_index));
+                let (i, k, odd) = lattice.cell(weights, cols);
+                // the center of a lattice triangle is a third of the way across it
+                let offset = if odd { 2.0 / 3.0 } else { 1.0 / 3.0 };
+                let di = weights.z * cols as f32 - (i as f32 + offset);
+                let dk = weights.y * cols as f32 - (k as f32 + offset);
+                assert!(
+                    di.abs() <= LATTICE_TOLERANCE && dk.abs() <= LATTICE_TOLERANCE,
+                    "Polyhedron isn't a subdivided icosahedron: face {} is off the lattice",
+                    face_index
+                );
+                let id = TileId::from_patch(&lattice_cell_patch(base, i, k, odd, cols));
+                assert!(
+                    seen.insert(id),
+                    "Polyhedron isn't a subdivided icosahedron: faces share the ID {:?}",
+                    id
+                );
+                id
+            })
+            .collect()
+    }
+}
+
+/// The patch of a triangle in the lattice of a base triangle with `cols` divisions per edge,
+/// found by following its centroid down through the quadrants.
+fn lattice_cell_patch(base: usize, i: usize, k: usize, odd: bool, cols: usize) -> Patch {
+    // centroid weights of the corners in thirds of the lattice
+    let offset = if odd { 2 } else { 1 };
+    let mut c = 3 * i as i64 + offset;
+    let mut b = 3 * k as i64 + offset;
+    let mut total = 3 * cols as i64;
+    let mut a = total - b - c;
+    let mut patch = Patch::new(base);
+    while total > 3 {
+        let half = total / 2;
+        let quadrant = if a > half {
+            a -= half;
+            0
+        } else if b > half {
+            b -= half;
+            1
+        } else if c > half {
+            c -= half;
+            2
+        } else {
+            let (mid_bc, mid_ca, mid_ab) = (half - a, half - b, half - c);
+            a = mid_bc;
+            b = mid_ca;
+            c = mid_ab;
+            3
+        };
+        patch = patch.child(quadrant);
+        total = half;
+    }
+    patch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Triangulation;
+
+    // these values are part of the stable interface and must never change
+
+    #[test]
+    fn patch_ids_are_stable() {
+        let patch = Patch {
+            base_face: 3,
+            path: vec![1, 2],
+        };
+        assert_eq!(TileId::from_patch(&patch), TileId(0x0da0_0000_0000_0000));
+        assert_eq!(TileId(0x0da0_0000_0000_0000).patch(), Some(patch));
+        assert_eq!(
+            TileId::from_patch(&Patch::new(19)),
+            TileId(0x4e00_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn hex_ids_are_stable() {
+        let coord = TileCoord {
+            base: 5,
+            q: 6,
+            r: 2,
+        };
+        assert_eq!(TileId::from_coord(coord, 8), TileId(0x9420_0000_0c00_0001));
+        assert_eq!(TileId(0x9420_0000_0c00_0001).coord(8), Some(coord));
+        let pentagon = TileCoord {
+            base: 0,
+            q: 0,
+            r: 0,
+        };
+        assert_eq!(
+            TileId::from_coord(pentagon, 4),
+            TileId(0x8000_0000_0000_0000)
+        );
+    }
+
+    #[test]
+    fn tiles_on_several_bases_have_one_id() {
+        let grid =
+            Polyhedron::new_truncated_isocahedron_with_triangulation(1.0, 2, Triangulation::Spokes)
+                .hex_grid();
+        // the same pentagon as a corner of base triangles 0, 6 and 15
+        let pentagon = grid
+            .face(TileCoord {
+                base: 6,
+                q: 0,
+                r: 0,
+            })
+            .unwrap();
+        for coord in [
+            TileCoord {
+                base: 0,
+                q: 0,
+                r: 4,
+            },
+            TileCoord {
+                base: 15,
+                q: 0,
+                r: 4,
+            },
+        ]
+        .iter()
+        {
+            assert_eq!(grid.face(*coord), Some(pentagon));
+        }
+        assert_eq!(grid.face_by_tile_id(grid.tile_id(pentagon)), Some(pentagon));
+        assert_eq!(grid.tile_id(pentagon), TileId(0x8000_0000_0000_0001));
+    }
+
+    #[test]
+    fn mesh_ids_are_stable() {
+        let ids = Polyhedron::new_isocahedron(1.0, 1).tile_ids();
+        assert_eq!(ids[0], TileId(0x0080_0000_0000_0000));
+        assert_eq!(ids[5], TileId(0x0780_0000_0000_0000));
+        assert_eq!(ids[79], TileId(0x4e80_0000_0000_0000));
+
+        let ids =
+            Polyhedron::new_truncated_isocahedron_with_triangulation(1.0, 1, Triangulation::Spokes)
+                .tile_ids();
+        assert_eq!(ids[0], TileId(0x8010_0000_0400_0000));
+        assert_eq!(ids[12], TileId(0x8c10_0000_0000_0001));
+        assert_eq!(ids[41], TileId(0xb810_0000_0400_0001));
+    }
+
+    #[test]
+    #[should_panic]
+    fn irregular_meshes_have_no_ids() {
+        let points = crate::PointDistribution::Fibonacci { count: 642 }.points();
+        Polyhedron::new_spherical_voronoi(1.0, &points, Triangulation::Spokes).tile_ids();
+    }
+}
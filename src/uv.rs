@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::f32::consts::PI;
+
+use cgmath::prelude::*;
+use cgmath::{Vector2, Vector3};
+
+use crate::{ArraySerializedVector2, Polyhedron};
+
+const UV_CACHE_PRECISION: f32 = 100000_f32;
+
+/// How texture coordinates are mapped onto the polyhedron.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum UvMode {
+    /// Longitude and latitude mapped to `u` and `v`. Vertices on the seam and at the poles are
+    /// split so that triangles never wrap around the texture.
+    Equirectangular,
+    /// Each triangle is projected onto the cube face it points at. The six faces are laid out
+    /// in a 3x2 atlas in the order +x, -x, +y (top row), -y, +z, -z (bottom row).
+    CubeMap,
+    /// Each face is mapped onto the unit square around `(0.5, 0.5)`, for texture atlases with
+    /// one image per tile.
+    TileAtlas,
+}
+
+impl Polyhedron {
+    /// Replaces `uvs` using the given mode, splitting vertices wherever neighboring triangles
+    /// need different texture coordinates for them.
+    pub fn compute_uvs(&mut self, mode: UvMode) {
+        self.uvs.clear();
+        let cell_faces = self.cell_face_indices();
+        let face_frames: Vec<TileFrame> = if mode == UvMode::TileAtlas {
+            (0..self.faces.len()).map(|i| self.tile_frame(i)).collect()
+        } else {
+            vec![]
+        };
+
+        let mut uvs: Vec<Option<Vector2<f32>>> = vec![None; self.positions.len()];
+        let mut split_cache: HashMap<(usize, (i32, i32)), usize> = HashMap::new();
+        for cell_index in 0..self.cells.len() {
+            let cell = &self.cells[cell_index];
+            let vertices = [cell.a, cell.b, cell.c];
+            let points = [
+                self.positions[cell.a].0,
+                self.positions[cell.b].0,
+                self.positions[cell.c].0,
+            ];
+            let cell_uvs = match mode {
+                UvMode::Equirectangular => equirectangular_uvs(&points),
+                UvMode::CubeMap => cube_map_uvs(&points),
+                UvMode::TileAtlas => {
+                    let frame = &face_frames[cell_faces[cell_index]];
+                    [
+                        frame.project(points[0]),
+                        frame.project(points[1]),
+                        frame.project(points[2]),
+                    ]
+                }
+            };
+
+            let mut new_vertices = vertices;
+            for (new_vertex, uv) in new_vertices.iter_mut().zip(cell_uvs.iter()) {
+                let uv_key = (
+                    (uv.x * UV_CACHE_PRECISION).round() as i32,
+                    (uv.y * UV_CACHE_PRECISION).round() as i32,
+                );
+                match uvs[*new_vertex] {
+                    None => {
+                        uvs[*new_vertex] = Some(*uv);
+                        split_cache.insert((*new_vertex, uv_key), *new_vertex);
+                    }
+                    Some(_) => {
+                        if let Some(split_vertex) = split_cache.get(&(*new_vertex, uv_key)) {
+                            *new_vertex = *split_vertex;
+                        } else {
+                            let split_vertex = self.duplicate_vertex(*new_vertex);
+                            uvs.push(Some(*uv));
+                            split_cache.insert((*new_vertex, uv_key), split_vertex);
+                            *new_vertex = split_vertex;
+                        }
+                    }
+                }
+            }
+            let cell = &mut self.cells[cell_index];
+            cell.a = new_vertices[0];
+            cell.b = new_vertices[1];
+            cell.c = new_vertices[2];
+        }
+
+        self.uvs = uvs
+            .iter()
+            .map(|uv| ArraySerializedVector2(uv.unwrap_or_else(|| Vector2::new(0.0, 0.0))))
+            .collect();
+    }
+
+    fn tile_frame(&self, face_index: usize) -> TileFrame {
+        let center = self.face_center(face_index);
+        let normal = self.face_normal(face_index);
+        let corners = self.face_corners(face_index);
+        let first_corner = self.positions[corners[0]].0 - center;
+        let u = (first_corner - normal * first_corner.dot(normal)).normalize();
+        let v = normal.cross(u);
+        let scale = corners
+            .iter()
+            .map(|corner| (self.positions[*corner].0 - center).magnitude())
+            .fold(0.0, f32::max);
+        TileFrame {
+            center,
+            u,
+            v,
+            scale,
+        }
+    }
+}
+
+/// Tangent plane of a face used to lay it out in the unit square.
+struct TileFrame {
+    center: Vector3<f32>,
+    u: Vector3<f32>,
+    v: Vector3<f32>,
+    scale: f32,
+}
+
+impl TileFrame {
+    fn project(&self, point: Vector3<f32>) -> Vector2<f32> {
+        let offset = point - self.center;
+        Vector2::new(
+            0.5 + 0.5 * offset.dot(self.u) / self.scale,
+            0.5 + 0.5 * offset.dot(self.v) / self.scale,
+        )
+    }
+}
+
+/// Longitude (around the y axis) and latitude of a point, both in radians.
+pub(crate) fn longitude_latitude(point: Vector3<f32>) -> (f32, f32) {
+    let latitude = (point.y / point.magnitude()).clamp(-1.0, 1.0).asin();
+    (point.z.atan2(point.x), latitude)
+}
+
+/// Unit vector pointing at a longitude and latitude, the inverse of `longitude_latitude`.
+pub(crate) fn direction_from_longitude_latitude(longitude: f32, latitude: f32) -> Vector3<f32> {
+    Vector3::new(
+        latitude.cos() * longitude.cos(),
+        latitude.sin(),
+        latitude.cos() * longitude.sin(),
+    )
+}
+
+fn is_pole(point: Vector3<f32>) -> bool {
+    point.x.hypot(point.z) < 1e-6 * point.magnitude()
+}
+
+fn equirectangular_uvs(points: &[Vector3<f32>; 3]) -> [Vector2<f32>; 3] {
+    let mut uvs = [Vector2::new(0.0, 0.0); 3];
+    for (uv, point) in uvs.iter_mut().zip(points.iter()) {
+        let (longitude, latitude) = longitude_latitude(*point);
+        *uv = Vector2::new(0.5 + longitude / (2.0 * PI), 0.5 + latitude / PI);
+    }
+
+    let not_poles: Vec<usize> = (0..3).filter(|i| !is_pole(points[*i])).collect();
+    let min_u = not_poles
+        .iter()
+        .map(|i| uvs[*i].x)
+        .fold(f32::INFINITY, f32::min);
+    let max_u = not_poles
+        .iter()
+        .map(|i| uvs[*i].x)
+        .fold(f32::NEG_INFINITY, f32::max);
+    // triangles that cross the seam wrap past u = 1 instead of spanning the whole texture
+    if max_u - min_u > 0.5 {
+        for i in not_poles.iter() {
+            if uvs[*i].x < 0.5 {
+                uvs[*i].x += 1.0;
+            }
+        }
+    }
+
+    if not_poles.len() < 3 && !not_poles.is_empty() {
+        let pole_u = not_poles.iter().map(|i| uvs[*i].x).sum::<f32>() / not_poles.len() as f32;
+        for i in 0..3 {
+            if is_pole(points[i]) {
+                uvs[i].x = pole_u;
+            }
+        }
+    }
+    uvs
+}
+
+fn cube_map_uvs(points: &[Vector3<f32>; 3]) -> [Vector2<f32>; 3] {
+    let centroid = points[0] + points[1] + points[2];
+    let abs = Vector3::new(centroid.x.abs(), centroid.y.abs(), centroid.z.abs());
+    let cube_face = if abs.x >= abs.y && abs.x >= abs.z {
+        if centroid.x >= 0.0 {
+            0
+        } else {
+            1
+        }
+    } else if abs.y >= abs.z {
+        if centroid.y >= 0.0 {
+            2
+        } else {
+            3
+        }
+    } else if centroid.z >= 0.0 {
+        4
+    } else {
+        5
+    };
+
+    let mut uvs = [Vector2::new(0.0, 0.0); 3];
+    for (uv, point) in uvs.iter_mut().zip(points.iter()) {
+        let (s, t, major) = match cube_face {
+            0 => (-point.z, -point.y, point.x),
+            1 => (point.z, -point.y, -point.x),
+            2 => (point.x, point.z, point.y),
+            3 => (point.x, -point.z, -point.y),
+            4 => (point.x, -point.y, point.z),
+            _ => (-point.x, -point.y, -point.z),
+        };
+        let s = (s / major + 1.0) / 2.0;
+        let t = (t / major + 1.0) / 2.0;
+        let column = (cube_face % 3) as f32;
+        let row = (cube_face / 3) as f32;
+        *uv = Vector2::new((column + s) / 3.0, (row + t) / 2.0);
+    }
+    uvs
+}